@@ -13,7 +13,7 @@ fn part1(input: &str) -> u32 {
         let first = digits
             .next()
             .expect("At least one digit should be in the line");
-        let last = digits.last().unwrap_or(first);
+        let last = digits.next_back().unwrap_or(first);
         sum += 10 * first + last;
     }
     sum
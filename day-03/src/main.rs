@@ -1,4 +1,7 @@
 use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+mod render;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum Token {
@@ -15,23 +18,84 @@ enum Token {
     },
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Neighbourhood {
+    Orthogonal,
+    Moore,            // full 8-neighbourhood
+    Chebyshev(usize), // everything within the given radius
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Adjacency {
+    neighbourhood: Neighbourhood,
+    wrap: bool, // toroidal: edges of the sheet touch the opposite edges
+}
+
+impl Default for Adjacency {
+    fn default() -> Self {
+        Self {
+            neighbourhood: Neighbourhood::Moore,
+            wrap: false,
+        }
+    }
+}
+
+impl Neighbourhood {
+    fn parse(spec: &str) -> Result<Self, String> {
+        match spec.split_once(':') {
+            None if spec == "orthogonal" => Ok(Self::Orthogonal),
+            None if spec == "moore" => Ok(Self::Moore),
+            Some(("chebyshev", radius)) => radius
+                .parse()
+                .map(Self::Chebyshev)
+                .map_err(|_| format!("invalid chebyshev radius {radius:?}")),
+            _ => Err(format!("unknown neighbourhood {spec:?}")),
+        }
+    }
+}
+
+impl Adjacency {
+    // Radii beyond the larger side of the sheet reach nothing new (with or
+    // without wrapping), so they are capped there
+    fn offsets(&self, width: usize, height: usize) -> Vec<(isize, isize)> {
+        let radius = match self.neighbourhood {
+            Neighbourhood::Orthogonal => return vec![(-1, 0), (1, 0), (0, -1), (0, 1)],
+            Neighbourhood::Moore => 1,
+            Neighbourhood::Chebyshev(radius) => radius.min(width.max(height)) as isize,
+        };
+        let mut offsets = Vec::new();
+        for i in -radius..=radius {
+            for j in -radius..=radius {
+                if (i, j) != (0, 0) {
+                    offsets.push((i, j));
+                }
+            }
+        }
+        offsets
+    }
+}
+
 #[derive(Debug)]
 struct Schematic {
     tokens: Vec<Token>,
     lut: HashMap<(isize, isize), usize>, // (row, col) -> index
+    width: usize,
+    height: usize,
+    adjacency: Adjacency,
+    offsets: Vec<(isize, isize)>, // cells within reach of a cell
 }
 
 impl Schematic {
-    fn parse(input: &str) -> Self {
+    fn parse(input: &str, adjacency: Adjacency) -> Self {
         let mut tokens = Vec::new();
         let mut lut = HashMap::new();
 
         let mut num_str = String::new();
 
-        // Instead of "lines.()" we leave the "\n" in the result
+        // Every line (including the last one) is terminated by a "\n"
         // for uniform handling of end of numbers.
-        for (row, line) in input.split_inclusive('\n').enumerate() {
-            for (col, ch) in line.chars().enumerate() {
+        for (row, line) in input.lines().enumerate() {
+            for (col, ch) in line.chars().chain(Some('\n')).enumerate() {
                 if ch.is_ascii_digit() {
                     num_str.push(ch);
                 } else {
@@ -63,12 +127,41 @@ impl Schematic {
             }
         }
 
-        Self { tokens, lut }
+        let width = input.lines().map(|l| l.chars().count()).max().unwrap_or(0);
+        let height = input.lines().count();
+
+        Self {
+            tokens,
+            lut,
+            width,
+            height,
+            adjacency,
+            offsets: adjacency.offsets(width, height),
+        }
     }
 
-    fn find_partnos(&self) -> Vec<&Token> {
-        let mut partnos = Vec::new();
-        for token in &self.tokens {
+    // Indices of all tokens touching the cells row/col_start..=col_end
+    fn neighbours(&self, row: usize, col_start: usize, col_end: usize) -> HashSet<usize> {
+        let mut idxs = HashSet::new();
+        for &(di, dj) in &self.offsets {
+            for col in col_start..=col_end {
+                let mut i = row as isize + di;
+                let mut j = col as isize + dj;
+                if self.adjacency.wrap {
+                    i = i.rem_euclid(self.height as isize);
+                    j = j.rem_euclid(self.width as isize);
+                }
+                if let Some(&idx) = self.lut.get(&(i, j)) {
+                    idxs.insert(idx);
+                }
+            }
+        }
+        idxs
+    }
+
+    fn find_partno_idxs(&self) -> HashSet<usize> {
+        let mut idxs = HashSet::new();
+        for (idx, token) in self.tokens.iter().enumerate() {
             if let &Token::Number {
                 row,
                 col_start,
@@ -76,70 +169,123 @@ impl Schematic {
                 ..
             } = token
             {
-                'search: for i in ((row as isize) - 1)..=((row as isize) + 1) {
-                    for j in ((col_start as isize) - 1)..=((col_end as isize) + 1) {
-                        if let Some(&idx) = self.lut.get(&(i, j)) {
-                            if let Token::Symbol { .. } = self.tokens[idx] {
-                                partnos.push(token);
-                                break 'search;
+                if self
+                    .neighbours(row, col_start, col_end)
+                    .into_iter()
+                    .any(|other| matches!(self.tokens[other], Token::Symbol { .. }))
+                {
+                    idxs.insert(idx);
+                }
+            }
+        }
+        idxs
+    }
+
+    fn find_partnos(&self) -> Vec<&Token> {
+        let idxs = self.find_partno_idxs();
+        self.tokens
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, token)| idxs.contains(&idx).then_some(token))
+            .collect()
+    }
+
+    // Returns (symbol index, gear ratio) for every '*' next to exactly two part numbers
+    fn find_gears(&self) -> Vec<(usize, u32)> {
+        let partnos = self.find_partno_idxs();
+        let mut gears = Vec::new();
+        for (idx, token) in self.tokens.iter().enumerate() {
+            if let &Token::Symbol { value, row, col } = token {
+                if value == '*' {
+                    let near_partnos_idxs: HashSet<_> = self
+                        .neighbours(row, col, col)
+                        .intersection(&partnos)
+                        .copied()
+                        .collect();
+                    if near_partnos_idxs.len() == 2 {
+                        let mut ratio = 1;
+                        for other in near_partnos_idxs {
+                            if let Token::Number { value, .. } = self.tokens[other] {
+                                ratio *= value;
                             }
                         }
+                        gears.push((idx, ratio));
                     }
                 }
             }
         }
-        partnos
+        gears
     }
 
+    fn partno_sum(&self) -> u32 {
+        self.find_partnos()
+            .iter()
+            .filter_map(|t| match t {
+                Token::Number { value, .. } => Some(*value),
+                _ => None,
+            })
+            .sum()
+    }
 
+    fn gear_ratio_sum(&self) -> u32 {
+        self.find_gears().iter().map(|(_, ratio)| ratio).sum()
+    }
 }
 
-fn part1(input: &str) -> u32 {
-    let schematic = Schematic::parse(input);
-    schematic.find_partnos().iter().filter_map(|t| match t {
-        Token::Number { value, ..} => Some(*value),
-        _ => None
-    }).sum()
+fn part1(input: &str, adjacency: Adjacency) -> u32 {
+    Schematic::parse(input, adjacency).partno_sum()
 }
 
-fn part2(input: &str) -> u32 {
-    let schematic = Schematic::parse(input);
-
-    let mut sum = 0;
-    let partnos = schematic.find_partnos();
-    for token in &schematic.tokens {
-        if let &Token::Symbol { value, row, col } = token {
-            if value == '*' {
-                let mut near_partnos_idxs = HashSet::new();
-                for i in ((row as isize) - 1)..=((row as isize) + 1) {
-                    for j in ((col as isize) - 1)..=((col as isize) + 1) {
-                        if let Some(&idx) = schematic.lut.get(&(i, j)) {
-                            if partnos.contains(&&schematic.tokens[idx]) {
-                                near_partnos_idxs.insert(idx);
-                            }
-                        }
-                    }
-                }
-                if near_partnos_idxs.len() == 2 {
-                    let mut ratio = 1;
-                    for idx in near_partnos_idxs {
-                        if let Token::Number { value, .. } = schematic.tokens[idx] {
-                            ratio *= value;
-                        }
-                    }
-                    sum += ratio;
-                }
+fn part2(input: &str, adjacency: Adjacency) -> u32 {
+    Schematic::parse(input, adjacency).gear_ratio_sum()
+}
+
+fn parse_args(args: &[String]) -> Result<(Option<Range<usize>>, Adjacency), String> {
+    let mut render = None;
+    let mut adjacency = Adjacency::default();
+
+    let mut args = args.iter().peekable();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--render" => {
+                let rows = match args.next_if(|a| !a.starts_with("--")) {
+                    Some(spec) => render::parse_rows(spec)?,
+                    None => 0..usize::MAX,
+                };
+                render = Some(rows);
+            }
+            "--adjacency" => {
+                let spec = args.next().ok_or("--adjacency needs a value")?;
+                adjacency.neighbourhood = Neighbourhood::parse(spec)?;
             }
+            "--wrap" => adjacency.wrap = true,
+            _ => return Err(format!("unknown argument {arg:?}")),
         }
-
     }
-    sum
+    Ok((render, adjacency))
 }
 
 fn main() {
     let text = include_str!("../inputs/input.txt");
-    println!("Part 1: {}", part1(text));
-    println!("Part 2: {}", part2(text));
+
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    let (render, adjacency) = parse_args(&args).unwrap_or_else(|err| {
+        eprintln!("{err}");
+        eprintln!(
+            "usage: day-03 [--render [START..END]] \
+             [--adjacency orthogonal|moore|chebyshev:R] [--wrap]"
+        );
+        std::process::exit(1);
+    });
+
+    if let Some(rows) = render {
+        let schematic = Schematic::parse(text, adjacency);
+        print!("{}", render::render(&schematic, text, rows));
+        return;
+    }
+
+    println!("Part 1: {}", part1(text, adjacency));
+    println!("Part 2: {}", part2(text, adjacency));
 }
 
 #[cfg(test)]
@@ -160,11 +306,100 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1(INPUT), 4361);
+        assert_eq!(part1(INPUT, Adjacency::default()), 4361);
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2(INPUT), 467835);
+        assert_eq!(part2(INPUT, Adjacency::default()), 467835);
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let args = |s: &str| s.split_whitespace().map(String::from).collect::<Vec<_>>();
+
+        assert_eq!(parse_args(&[]), Ok((None, Adjacency::default())));
+        assert_eq!(
+            parse_args(&args("--render 2..5 --adjacency chebyshev:3 --wrap")),
+            Ok((
+                Some(2..5),
+                Adjacency {
+                    neighbourhood: Neighbourhood::Chebyshev(3),
+                    wrap: true
+                }
+            ))
+        );
+        assert_eq!(
+            parse_args(&args("--render --adjacency orthogonal")),
+            Ok((
+                Some(0..usize::MAX),
+                Adjacency {
+                    neighbourhood: Neighbourhood::Orthogonal,
+                    wrap: false
+                }
+            ))
+        );
+        assert!(parse_args(&args("--adjacency hex")).is_err());
+        assert!(parse_args(&args("--adjacency")).is_err());
+    }
+
+    fn solve(input: &str, neighbourhood: Neighbourhood, wrap: bool) -> (u32, u32) {
+        let adjacency = Adjacency {
+            neighbourhood,
+            wrap,
+        };
+        (part1(input, adjacency), part2(input, adjacency))
+    }
+
+    #[test]
+    fn test_orthogonal() {
+        let input = "\
+            1....\n\
+            .*...\n\
+            ..2..\n\
+            .....\n\
+            3*4..";
+
+        assert_eq!(solve(input, Neighbourhood::Moore, false), (10, 14));
+        assert_eq!(solve(input, Neighbourhood::Orthogonal, false), (7, 12));
+        assert_eq!(solve(INPUT, Neighbourhood::Orthogonal, false), (2547, 0));
+    }
+
+    #[test]
+    fn test_chebyshev() {
+        let input = "\
+            5....\n\
+            .....\n\
+            ..*..\n\
+            .....\n\
+            ....7";
+
+        assert_eq!(solve(input, Neighbourhood::Moore, false), (0, 0));
+        assert_eq!(solve(input, Neighbourhood::Chebyshev(1), false), (0, 0));
+        assert_eq!(solve(input, Neighbourhood::Chebyshev(2), false), (12, 35));
+        assert_eq!(
+            solve(INPUT, Neighbourhood::Chebyshev(1), false),
+            (4361, 467835)
+        );
+        // Radii larger than the sheet behave like the largest useful one
+        for wrap in [false, true] {
+            assert_eq!(
+                solve(INPUT, Neighbourhood::Chebyshev(usize::MAX), wrap),
+                solve(INPUT, Neighbourhood::Chebyshev(10), wrap)
+            );
+        }
+    }
+
+    #[test]
+    fn test_wrap() {
+        let input = "\
+            3...*\n\
+            .....\n\
+            ....4";
+
+        assert_eq!(solve(input, Neighbourhood::Moore, false), (0, 0));
+        assert_eq!(solve(input, Neighbourhood::Moore, true), (7, 12));
+        assert_eq!(solve(input, Neighbourhood::Orthogonal, true), (7, 12));
+        assert_eq!(solve(INPUT, Neighbourhood::Moore, true), (4361, 467835));
     }
 }
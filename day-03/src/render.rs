@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::ops::Range;
+
+use super::{Schematic, Token};
+
+const RESET: &str = "\x1b[0m";
+const PARTNO: &str = "\x1b[32m";
+const NON_PARTNO: &str = "\x1b[2m";
+const GEAR: &str = "\x1b[1;33m";
+const SYMBOL: &str = "\x1b[36m";
+
+// Accepts "START..END", "START.." or "..END" (0-based, END exclusive)
+pub fn parse_rows(spec: &str) -> Result<Range<usize>, String> {
+    let (start, end) = spec
+        .split_once("..")
+        .ok_or_else(|| format!("invalid row range {spec:?}, expected START..END"))?;
+    let bound = |s: &str, default| {
+        if s.is_empty() {
+            Ok(default)
+        } else {
+            s.parse()
+                .map_err(|_| format!("invalid row bound {s:?} in {spec:?}"))
+        }
+    };
+    Ok(bound(start, 0)?..bound(end, usize::MAX)?)
+}
+
+pub fn render(schematic: &Schematic, input: &str, rows: Range<usize>) -> String {
+    let partnos = schematic.find_partno_idxs();
+    let gears: HashMap<_, _> = schematic.find_gears().into_iter().collect();
+
+    let style = |idx: usize| match schematic.tokens[idx] {
+        Token::Number { .. } if partnos.contains(&idx) => PARTNO,
+        Token::Number { .. } => NON_PARTNO,
+        Token::Symbol { .. } if gears.contains_key(&idx) => GEAR,
+        Token::Symbol { .. } => SYMBOL,
+    };
+
+    let mut output = String::new();
+    for (row, line) in input.lines().enumerate() {
+        if !rows.contains(&row) {
+            continue;
+        }
+        let mut current = None;
+        let mut ratios = Vec::new();
+        for (col, ch) in line.chars().enumerate() {
+            let idx = schematic.lut.get(&(row as isize, col as isize)).copied();
+            let next = idx.map(style);
+            if next != current {
+                if current.is_some() {
+                    output.push_str(RESET);
+                }
+                if let Some(next) = next {
+                    output.push_str(next);
+                }
+                current = next;
+            }
+            output.push(ch);
+            if let Some(ratio) = idx.and_then(|idx| gears.get(&idx)) {
+                ratios.push(format!("{GEAR}*{RESET}@{col}={ratio}"));
+            }
+        }
+        if current.is_some() {
+            output.push_str(RESET);
+        }
+        if !ratios.is_empty() {
+            write!(output, "  {}", ratios.join(" ")).unwrap();
+        }
+        output.push('\n');
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Adjacency;
+
+    const INPUT: &str = "\
+        467..114..\n\
+        ...*......\n\
+        ..35..633.";
+
+    #[test]
+    fn test_parse_rows() {
+        assert_eq!(parse_rows("3..7"), Ok(3..7));
+        assert_eq!(parse_rows("3.."), Ok(3..usize::MAX));
+        assert_eq!(parse_rows("..7"), Ok(0..7));
+        assert!(parse_rows("3-7").is_err());
+        assert!(parse_rows("a..7").is_err());
+    }
+
+    #[test]
+    fn test_render() {
+        let schematic = Schematic::parse(INPUT, Adjacency::default());
+        assert_eq!(
+            render(&schematic, INPUT, 1..3),
+            format!(
+                "...{GEAR}*{RESET}......  {GEAR}*{RESET}@3=16345\n\
+                 ..{PARTNO}35{RESET}..{NON_PARTNO}633{RESET}.\n"
+            )
+        );
+    }
+}
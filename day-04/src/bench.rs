@@ -0,0 +1,72 @@
+use std::time::{Duration, Instant};
+
+use super::{count_matches, Card};
+
+// Small xorshift generator, good enough for synthetic card piles
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed.max(1))
+    }
+
+    pub fn below(&mut self, bound: u32) -> u32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % bound as u64) as u32
+    }
+
+    fn distinct(&mut self, count: usize, bound: u32) -> Vec<u32> {
+        let mut values = Vec::with_capacity(count);
+        while values.len() < count {
+            let v = self.below(bound);
+            if !values.contains(&v) {
+                values.push(v);
+            }
+        }
+        values
+    }
+}
+
+// Cards shaped like the puzzle input (10 winners, 25 numbers), values below
+// `bound` (which has to be at least 25)
+pub fn generate_pile(n: usize, bound: u32, rng: &mut Rng) -> Vec<Card> {
+    (1..=n)
+        .map(|id| Card {
+            id,
+            winners: rng.distinct(10, bound),
+            numbers: rng.distinct(25, bound),
+        })
+        .collect()
+}
+
+fn time<T>(f: impl Fn() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let result = f();
+    (result, start.elapsed())
+}
+
+pub fn run(n: usize) {
+    let cards = generate_pile(n, 100, &mut Rng::new(2023));
+    println!("{n} generated cards (values below 100)");
+
+    let (reference, t_hashset) =
+        time(|| cards.iter().map(Card::matches_hashset).collect::<Vec<_>>());
+    let (per_card, t_bitset) = time(|| cards.iter().map(Card::matches).collect::<Vec<_>>());
+    let (batched, t_batched) = time(|| count_matches(&cards));
+    assert_eq!(reference, per_card);
+    assert_eq!(reference, batched);
+
+    for (name, t) in [
+        ("HashSet", t_hashset),
+        ("bitset", t_bitset),
+        ("bitset, batched", t_batched),
+    ] {
+        println!(
+            "{name:>16}: {:>10.3?} ({:.1}x)",
+            t,
+            t_hashset.as_secs_f64() / t.as_secs_f64()
+        );
+    }
+}
@@ -0,0 +1,41 @@
+// Fixed-size bitset over the values 0..128*N
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BitSet<const N: usize>([u128; N]);
+
+impl<const N: usize> BitSet<N> {
+    pub const CAPACITY: u32 = 128 * N as u32;
+
+    // Panics if a value does not fit, callers pick N from the largest value
+    pub fn new(values: &[u32]) -> Self {
+        let mut words = [0; N];
+        for &v in values {
+            words[(v / 128) as usize] |= 1 << (v % 128);
+        }
+        Self(words)
+    }
+
+    pub fn intersection_len(&self, other: &Self) -> usize {
+        self.0
+            .iter()
+            .zip(&other.0)
+            .map(|(a, b)| (a & b).count_ones() as usize)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intersection_len() {
+        let a = BitSet::<1>::new(&[0, 5, 17, 127]);
+        let b = BitSet::<1>::new(&[5, 6, 127]);
+        assert_eq!(a.intersection_len(&b), 2);
+
+        let a = BitSet::<4>::new(&[1, 128, 300, 511]);
+        let b = BitSet::<4>::new(&[128, 129, 511]);
+        assert_eq!(a.intersection_len(&b), 2);
+        assert_eq!(BitSet::<4>::CAPACITY, 512);
+    }
+}
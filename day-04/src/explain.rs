@@ -0,0 +1,142 @@
+use num_bigint::BigUint;
+use num_traits::One;
+
+use super::{cascade_with, count_matches, points, Card};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CardReport {
+    pub id: usize,
+    pub matches: usize,
+    pub points: BigUint,
+    pub copies: BigUint,
+    pub contributors: Vec<(usize, BigUint)>, // (earlier card id, copies it gave us)
+}
+
+impl CardReport {
+    fn contributors_str(&self) -> String {
+        self.contributors
+            .iter()
+            .map(|(id, n)| format!("{id}:{n}"))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+// Expects a validated deck (see `parse_deck`)
+pub fn explain(cards: &[Card]) -> Vec<CardReport> {
+    let mut reports: Vec<_> = cards
+        .iter()
+        .zip(count_matches(cards))
+        .map(|(card, matches)| CardReport {
+            id: card.id,
+            matches,
+            points: points(matches).unwrap(),
+            copies: BigUint::one(),
+            contributors: Vec::new(),
+        })
+        .collect();
+
+    cascade_with(cards, |from, to, n: &BigUint| {
+        let report = &mut reports[to - 1];
+        report.copies += n;
+        report.contributors.push((from, n.clone()));
+    });
+    reports
+}
+
+pub fn to_table(reports: &[CardReport]) -> String {
+    let header = ["card", "matches", "points", "copies", "contributors"];
+    let rows: Vec<[String; 5]> = reports
+        .iter()
+        .map(|r| {
+            [
+                r.id.to_string(),
+                r.matches.to_string(),
+                r.points.to_string(),
+                r.copies.to_string(),
+                r.contributors_str(),
+            ]
+        })
+        .collect();
+
+    let mut widths = header.map(str::len);
+    for row in &rows {
+        for (w, cell) in widths.iter_mut().zip(row) {
+            *w = (*w).max(cell.len());
+        }
+    }
+
+    let mut table = String::new();
+    let mut push_row = |cells: [&str; 5]| {
+        // Numbers are right aligned, the contributors list is not
+        let mut line = String::new();
+        for (i, (cell, w)) in cells.iter().zip(widths).enumerate() {
+            match i {
+                0 => line.push_str(&format!("{cell:>w$}")),
+                4 => line.push_str(&format!("  {cell}")),
+                _ => line.push_str(&format!("  {cell:>w$}")),
+            }
+        }
+        table.push_str(line.trim_end());
+        table.push('\n');
+    };
+    push_row(header);
+    push_row(widths.map(|w| "-".repeat(w)).each_ref().map(String::as_str));
+    for row in &rows {
+        push_row(row.each_ref().map(String::as_str));
+    }
+    table
+}
+
+pub fn to_csv(reports: &[CardReport]) -> String {
+    let mut csv = String::from("card,matches,points,copies,contributors\n");
+    for r in reports {
+        csv.push_str(&format!(
+            "{},{},{},{},{}\n",
+            r.id,
+            r.matches,
+            r.points,
+            r.copies,
+            r.contributors_str()
+        ));
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_deck;
+
+    const INPUT: &str = "\
+        Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53\n\
+        Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19\n\
+        Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1\n\
+        Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83\n\
+        Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36\n\
+        Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
+
+    #[test]
+    fn test_explain() {
+        let reports = explain(&parse_deck(INPUT).unwrap());
+        assert_eq!(
+            to_csv(&reports),
+            "\
+            card,matches,points,copies,contributors\n\
+            1,4,8,1,\n\
+            2,2,2,2,1:1\n\
+            3,2,2,4,1:1 2:2\n\
+            4,1,1,8,1:1 2:2 3:4\n\
+            5,0,0,14,1:1 3:4 4:8\n\
+            6,0,0,1,\n"
+        );
+        assert_eq!(
+            to_table(&reports[..2]),
+            "\
+            card  matches  points  copies  contributors\n\
+            ----  -------  ------  ------  ------------\n   \
+            1        4       8       1\n   \
+            2        2       2       2  1:1\n"
+        );
+    }
+}
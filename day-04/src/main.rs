@@ -1,23 +1,188 @@
-use std::collections::{HashMap, HashSet};
-
-fn count_wins(card: &str) -> u64 {
-    let (winners, numbers) = card.split_once(':').unwrap().1.split_once('|').unwrap();
-    let winners: HashSet<_> = winners.split_whitespace().collect();
-    numbers
-        .split_whitespace()
-        .filter(|n| winners.contains(n))
-        .count() as u64
-}
-
-fn part1(input: &str) -> u32 {
-    let mut value = 0;
-    for line in input.lines() {
-        let wins = count_wins(line);
-        if wins > 0 {
-            value += 1 << (wins - 1);
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+
+use num_bigint::BigUint;
+use num_traits::{checked_pow, CheckedAdd, CheckedMul, One, Zero};
+
+use bitset::BitSet;
+
+mod bench;
+mod bitset;
+mod explain;
+
+#[derive(Debug, Clone, PartialEq)]
+enum DeckError {
+    Syntax { line: usize, reason: String },
+    Duplicate { id: usize, lines: (usize, usize) },
+    Missing { id: usize },
+}
+
+impl fmt::Display for DeckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax { line, reason } => write!(f, "line {line}: {reason}"),
+            Self::Duplicate { id, lines } => write!(
+                f,
+                "card {id} appears twice (lines {} and {})",
+                lines.0, lines.1
+            ),
+            Self::Missing { id } => write!(f, "card {id} is missing"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Card {
+    id: usize,
+    winners: Vec<u32>,
+    numbers: Vec<u32>,
+}
+
+impl Card {
+    // `line` is only used for error reporting (1-based)
+    fn parse(text: &str, line: usize) -> Result<Self, DeckError> {
+        let error = |reason: String| DeckError::Syntax { line, reason };
+
+        let (header, body) = text
+            .split_once(':')
+            .ok_or_else(|| error("missing ':' after the card header".into()))?;
+        let id = header
+            .strip_prefix("Card")
+            .and_then(|id| id.trim().parse().ok())
+            .ok_or_else(|| error(format!("invalid card header {header:?}")))?;
+        let (winners, numbers) = body
+            .split_once('|')
+            .ok_or_else(|| error("missing '|' between winners and numbers".into()))?;
+
+        // Numbers never repeat within a list, so counting matches per number
+        // and per distinct number agree
+        let parse_numbers = |s: &str, list| {
+            let mut numbers = Vec::new();
+            for n in s.split_whitespace() {
+                let value = n
+                    .parse()
+                    .map_err(|_| error(format!("invalid number {n:?}")))?;
+                if numbers.contains(&value) {
+                    return Err(error(format!("{value} appears twice among the {list}")));
+                }
+                numbers.push(value);
+            }
+            Ok(numbers)
+        };
+
+        Ok(Self {
+            id,
+            winners: parse_numbers(winners, "winners")?,
+            numbers: parse_numbers(numbers, "numbers")?,
+        })
+    }
+
+    // Number of winning numbers we have
+    fn matches(&self) -> usize {
+        let max = self.winners.iter().chain(&self.numbers).max();
+        match max.copied().unwrap_or(0) {
+            v if v < BitSet::<1>::CAPACITY => self.matches_bitset::<1>(),
+            v if v < BitSet::<2>::CAPACITY => self.matches_bitset::<2>(),
+            v if v < BitSet::<4>::CAPACITY => self.matches_bitset::<4>(),
+            v if v < BitSet::<8>::CAPACITY => self.matches_bitset::<8>(),
+            _ => self.matches_hashset(),
+        }
+    }
+
+    fn matches_bitset<const N: usize>(&self) -> usize {
+        BitSet::<N>::new(&self.winners).intersection_len(&BitSet::new(&self.numbers))
+    }
+
+    // Reference implementation: every number we have that is a winner
+    fn matches_hashset(&self) -> usize {
+        let winners: HashSet<_> = self.winners.iter().collect();
+        self.numbers.iter().filter(|n| winners.contains(n)).count()
+    }
+}
+
+// Matches for a whole pile. When every value fits into 128 bits the cards are
+// packed into flat arrays first, so that the AND + popcount loop vectorises.
+fn count_matches(cards: &[Card]) -> Vec<usize> {
+    let fits = cards
+        .iter()
+        .flat_map(|c| c.winners.iter().chain(&c.numbers))
+        .all(|&v| v < BitSet::<1>::CAPACITY);
+    if !fits {
+        return cards.iter().map(Card::matches).collect();
+    }
+
+    let pack = |values: &[u32]| values.iter().fold(0u128, |acc, v| acc | 1 << v);
+    let winners: Vec<_> = cards.iter().map(|c| pack(&c.winners)).collect();
+    let numbers: Vec<_> = cards.iter().map(|c| pack(&c.numbers)).collect();
+    winners
+        .iter()
+        .zip(&numbers)
+        .map(|(w, n)| (w & n).count_ones() as usize)
+        .collect()
+}
+
+// Parses every card and checks that the ids are exactly 1..=N (in any order).
+// The result is sorted by id.
+fn parse_deck(input: &str) -> Result<Vec<Card>, DeckError> {
+    let mut deck = BTreeMap::new();
+    for (i, text) in input.lines().enumerate() {
+        let card = Card::parse(text, i + 1)?;
+        if let Some((line, _)) = deck.get(&card.id) {
+            return Err(DeckError::Duplicate {
+                id: card.id,
+                lines: (*line, i + 1),
+            });
         }
+        deck.insert(card.id, (i + 1, card));
     }
-    value
+
+    if let Some(id) = (1..)
+        .zip(deck.keys())
+        .find_map(|(id, &k)| (id != k).then_some(id))
+    {
+        return Err(DeckError::Missing { id });
+    }
+    Ok(deck.into_values().map(|(_, card)| card).collect())
+}
+
+// Anything that can count cards and points; `None` signals an overflow
+trait Count: Clone + Zero + One + CheckedAdd + CheckedMul {}
+
+impl<T: Clone + Zero + One + CheckedAdd + CheckedMul> Count for T {}
+
+// Computes with u64 first and only redoes the work with BigUint if that overflowed
+fn exact(
+    compute: impl Fn(&[Card]) -> Option<u64>,
+    compute_big: impl Fn(&[Card]) -> Option<BigUint>,
+    cards: &[Card],
+) -> BigUint {
+    compute(cards)
+        .map(BigUint::from)
+        .or_else(|| compute_big(cards))
+        .expect("arbitrary precision arithmetic cannot overflow")
+}
+
+fn points<T: Count>(wins: usize) -> Option<T> {
+    if wins == 0 {
+        Some(T::zero())
+    } else {
+        checked_pow(T::one() + T::one(), wins - 1)
+    }
+}
+
+fn score<T: Count>(cards: &[Card]) -> Option<T> {
+    count_matches(cards)
+        .into_iter()
+        .try_fold(T::zero(), |acc, wins| acc.checked_add(&points(wins)?))
+}
+
+fn part1(input: &str) -> Result<BigUint, DeckError> {
+    let cards = input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| Card::parse(line, i + 1))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(exact(score::<u64>, score::<BigUint>, &cards))
 }
 
 // For iterator fan boys
@@ -44,27 +209,79 @@ fn part1_with_iterators(input: &str) -> u32 {
         .sum()
 }
 
-fn part2(input: &str) -> u32 {
+fn cascade<T: Count>(cards: &[Card]) -> Option<T> {
+    cascade_with(cards, |_, _, _| {})
+}
+
+// `on_copy(from, to, n)` is called whenever card `from` wins `n` copies of card `to`
+fn cascade_with<T: Count>(cards: &[Card], mut on_copy: impl FnMut(usize, usize, &T)) -> Option<T> {
     let mut deck = HashMap::new();
 
-    for (i_current, line) in input.lines().enumerate() {
-        let n_current = deck.entry(i_current).or_insert(0);
-        *n_current += 1;
-        let multiplier = *n_current;
-        let win_range = count_wins(line) as usize;
+    for (card, wins) in cards.iter().zip(count_matches(cards)) {
+        let n_current = deck.entry(card.id).or_insert_with(T::zero);
+        *n_current = n_current.checked_add(&T::one())?;
+        let multiplier = n_current.clone();
+        // Copies never reach past the end of the table
+        let win_range = wins.min(cards.len() - card.id);
 
-        for idx in (i_current + 1)..=(i_current + win_range) {
-            *deck.entry(idx).or_insert(0) += multiplier;
+        for id in (card.id + 1)..=(card.id + win_range) {
+            let n = deck.entry(id).or_insert_with(T::zero);
+            *n = n.checked_add(&multiplier)?;
+            on_copy(card.id, id, &multiplier);
         }
     }
-    
-    deck.values().sum::<u32>()
+
+    deck.values()
+        .try_fold(T::zero(), |acc, n| acc.checked_add(n))
 }
 
+fn part2(input: &str) -> Result<BigUint, DeckError> {
+    let cards = parse_deck(input)?;
+    Ok(exact(cascade::<u64>, cascade::<BigUint>, &cards))
+}
+
+const USAGE: &str = "usage: day-04 [--bench | --explain [--csv]]";
+
 fn main() {
     let text = include_str!("../inputs/input.txt");
-    println!("Part 1: {}", part1(text));
-    println!("Part 2: {}", part2(text));
+
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        [] => (),
+        // Best run with --release
+        ["--bench"] => return bench::run(1_000_000),
+        ["--explain", ref format @ ..] if format.len() <= 1 => {
+            let report = parse_deck(text).map(|cards| explain::explain(&cards));
+            match (report, format) {
+                (Ok(report), []) => print!("{}", explain::to_table(&report)),
+                (Ok(report), ["--csv"]) => print!("{}", explain::to_csv(&report)),
+                (Err(err), _) => {
+                    eprintln!("Error: {err}");
+                    std::process::exit(1);
+                }
+                _ => {
+                    eprintln!("{USAGE}");
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        _ => {
+            eprintln!("{USAGE}");
+            std::process::exit(1);
+        }
+    }
+
+    match (part1(text), part2(text)) {
+        (Ok(p1), Ok(p2)) => {
+            println!("Part 1: {p1}");
+            println!("Part 2: {p2}");
+        }
+        (Err(err), _) | (_, Err(err)) => {
+            eprintln!("Error: {err}");
+            std::process::exit(1);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -81,11 +298,107 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1(INPUT), 13);
+        assert_eq!(part1(INPUT), Ok(13u32.into()));
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2(INPUT), 30);
+        assert_eq!(part2(INPUT), Ok(30u32.into()));
+    }
+
+    #[test]
+    fn test_part2_unordered() {
+        let mut lines: Vec<_> = INPUT.lines().collect();
+        lines.reverse();
+        assert_eq!(part2(&lines.join("\n")), Ok(30u32.into()));
+    }
+
+    // Card i wins copies of every later card, so it ends up with 2^(i-1) copies
+    fn chain(n: usize) -> String {
+        (1..=n)
+            .map(|i| {
+                let wins = (1..=n - i)
+                    .map(|w| w.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                format!("Card {i}: {wins} | {wins}")
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_long_chains() {
+        let pow2 = |e: u32| BigUint::from(2u32).pow(e);
+        let one = BigUint::one;
+
+        // Just fits into u64
+        assert_eq!(part1(&chain(64)), Ok(pow2(63) - one()));
+        assert_eq!(part2(&chain(64)), Ok(pow2(64) - one()));
+        assert_eq!(
+            cascade::<u64>(&parse_deck(&chain(64)).unwrap()),
+            Some(u64::MAX)
+        );
+
+        // Overflows u64 and falls back to BigUint
+        assert_eq!(cascade::<u64>(&parse_deck(&chain(65)).unwrap()), None);
+        assert_eq!(part2(&chain(65)), Ok(pow2(65) - one()));
+
+        for n in [100, 500] {
+            assert_eq!(part1(&chain(n)), Ok(pow2(n as u32 - 1) - one()));
+            assert_eq!(part2(&chain(n)), Ok(pow2(n as u32) - one()));
+        }
+    }
+
+    #[test]
+    fn test_matches_reference() {
+        let mut rng = bench::Rng::new(42);
+        // Covers every bitset width and the HashSet fallback
+        for bound in [40, 100, 128, 200, 500, 1000, 5000] {
+            let cards = bench::generate_pile(200, bound, &mut rng);
+            let reference: Vec<_> = cards.iter().map(Card::matches_hashset).collect();
+            assert_eq!(
+                cards.iter().map(Card::matches).collect::<Vec<_>>(),
+                reference
+            );
+            assert_eq!(count_matches(&cards), reference);
+        }
+    }
+
+    #[test]
+    fn test_invalid_deck() {
+        let lines: Vec<_> = INPUT.lines().collect();
+
+        let gap = [lines[0], lines[1], lines[3]].join("\n");
+        assert_eq!(part2(&gap), Err(DeckError::Missing { id: 3 }));
+
+        let duplicate = [lines[0], lines[1], lines[0]].join("\n");
+        assert_eq!(
+            part2(&duplicate),
+            Err(DeckError::Duplicate {
+                id: 1,
+                lines: (1, 3)
+            })
+        );
+
+        let garbage = [lines[0], "Card x: 1 | 2"].join("\n");
+        assert!(matches!(
+            part1(&garbage),
+            Err(DeckError::Syntax { line: 2, .. })
+        ));
+        assert!(matches!(
+            part2("Card 1: 1 2 3"),
+            Err(DeckError::Syntax { line: 1, .. })
+        ));
+
+        let repeated = [lines[0], "Card 2: 1 2 | 3 1 1"].join("\n");
+        assert_eq!(
+            part1(&repeated).unwrap_err().to_string(),
+            "line 2: 1 appears twice among the numbers"
+        );
+        assert_eq!(
+            part1("Card 1: 5 5 | 5").unwrap_err().to_string(),
+            "line 1: 5 appears twice among the winners"
+        );
     }
 }
@@ -0,0 +1,143 @@
+use super::Interval;
+
+// Sorted, disjoint, non-empty intervals; touching intervals are merged
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct IntervalSet(Vec<Interval>);
+
+impl IntervalSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Interval> {
+        self.0.iter()
+    }
+
+    // Number of fragments
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn min(&self) -> Option<u64> {
+        self.0.first().map(|r| r.start)
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        self.iter().chain(other.iter()).copied().collect()
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut result = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while let (Some(a), Some(b)) = (self.0.get(i), other.0.get(j)) {
+            if let Some(overlap) = a.overlap(b) {
+                result.push(overlap);
+            }
+            if a.end <= b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        Self(result)
+    }
+
+    // Everything inside `bound` that is not in the set
+    pub fn complement(&self, bound: &Interval) -> Self {
+        let mut result = Vec::new();
+        let mut start = bound.start;
+        for r in &self.0 {
+            if r.start > start {
+                result.push(Interval::new(start, r.start.min(bound.end)));
+            }
+            start = start.max(r.end);
+        }
+        result.push(Interval::new(start, bound.end));
+        result.into_iter().collect()
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        if self.is_empty() {
+            return Self::new();
+        }
+        let bound = Interval::new(self.0[0].start, self.0[self.len() - 1].end);
+        self.intersection(&other.complement(&bound))
+    }
+}
+
+impl FromIterator<Interval> for IntervalSet {
+    fn from_iter<I: IntoIterator<Item = Interval>>(iter: I) -> Self {
+        let mut intervals: Vec<_> = iter.into_iter().filter(|r| !r.is_empty()).collect();
+        intervals.sort_by_key(|r| r.start);
+
+        let mut merged: Vec<Interval> = Vec::with_capacity(intervals.len());
+        for r in intervals {
+            match merged.last_mut() {
+                Some(last) if last.end >= r.start => last.end = last.end.max(r.end),
+                _ => merged.push(r),
+            }
+        }
+        Self(merged)
+    }
+}
+
+impl IntoIterator for IntervalSet {
+    type Item = Interval;
+    type IntoIter = std::vec::IntoIter<Interval>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(intervals: &[(u64, u64)]) -> IntervalSet {
+        intervals
+            .iter()
+            .map(|&(s, e)| Interval::new(s, e))
+            .collect()
+    }
+
+    #[test]
+    fn test_normalise() {
+        assert_eq!(
+            set(&[(10, 20), (0, 5), (5, 7), (15, 25), (30, 30), (40, 41)]),
+            IntervalSet(vec![
+                Interval::new(0, 7),
+                Interval::new(10, 25),
+                Interval::new(40, 41)
+            ])
+        );
+        assert!(set(&[(3, 3), (5, 2)]).is_empty());
+    }
+
+    #[test]
+    fn test_algebra() {
+        let a = set(&[(0, 10), (20, 30)]);
+        let b = set(&[(5, 25), (40, 50)]);
+
+        assert_eq!(a.union(&b), set(&[(0, 30), (40, 50)]));
+        assert_eq!(a.intersection(&b), set(&[(5, 10), (20, 25)]));
+        assert_eq!(a.difference(&b), set(&[(0, 5), (25, 30)]));
+        assert_eq!(b.difference(&a), set(&[(10, 20), (40, 50)]));
+        assert_eq!(
+            a.complement(&Interval::new(0, 100)),
+            set(&[(10, 20), (30, 100)])
+        );
+        assert_eq!(a.complement(&Interval::new(5, 25)), set(&[(10, 20)]));
+        assert_eq!(
+            IntervalSet::new().complement(&Interval::new(1, 2)),
+            set(&[(1, 2)])
+        );
+        assert!(a.difference(&a).is_empty());
+
+        assert_eq!(b.min(), Some(5));
+    }
+}
@@ -1,7 +1,18 @@
-use std::{cmp::Ordering, ops::Add};
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, VecDeque},
+    fmt,
+    ops::Add,
+};
 
 use itertools::Itertools;
 
+use interval_set::IntervalSet;
+
+mod interval_set;
+#[cfg(test)]
+mod reference;
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 struct Interval {
     start: u64,
@@ -26,16 +37,32 @@ impl Interval {
     fn is_empty(&self) -> bool {
         self.end <= self.start
     }
+
+    // Starting at `start`, `len` long
+    fn checked_new(start: u64, len: u64) -> Option<Self> {
+        Some(Self::new(start, start.checked_add(len)?))
+    }
+
+    // None if any end would leave the u64 range
+    fn translate(&self, offset: i128) -> Option<Self> {
+        Some(Self::new(
+            shift(self.start, offset)?,
+            shift(self.end, offset)?,
+        ))
+    }
+}
+
+fn shift(value: u64, offset: i128) -> Option<u64> {
+    u64::try_from(value as i128 + offset).ok()
 }
 
+// Rules are range checked while parsing, so only a bug could get us out of range here
 impl Add<i128> for Interval {
     type Output = Self;
 
     fn add(self, offset: i128) -> Self {
-        Interval::new(
-            (self.start as i128 + offset) as u64,
-            (self.end as i128 + offset) as u64,
-        )
+        self.translate(offset)
+            .unwrap_or_else(|| panic!("{self:?} shifted by {offset} leaves the u64 range"))
     }
 }
 
@@ -55,60 +82,383 @@ impl PartialOrd for Interval {
 
 type IntervalMap = Vec<(Interval, i128)>;
 
+// Splits [0, u64::MAX) into consecutive pieces with their offsets (0 = unmapped)
+fn segments(map: &IntervalMap) -> IntervalMap {
+    let mut segments = IntervalMap::new();
+    let mut start = 0;
+    for &(src, offset) in map {
+        if start < src.start {
+            segments.push((Interval::new(start, src.start), 0));
+        }
+        segments.push((src, offset));
+        start = src.end;
+    }
+    if start < u64::MAX {
+        segments.push((Interval::new(start, u64::MAX), 0));
+    }
+    segments
+}
+
+// The map equivalent to applying `first` and then `second`
+fn compose(first: &IntervalMap, second: &IntervalMap) -> IntervalMap {
+    let second = segments(second);
+    let mut composed = IntervalMap::new();
+    for (src, offset) in segments(first) {
+        let image = src + offset;
+        for &(src2, offset2) in &second {
+            if let Some(overlap) = image.overlap(&src2) {
+                if offset + offset2 != 0 {
+                    composed.push((overlap + -offset, offset + offset2));
+                }
+            }
+        }
+    }
+    composed.sort_by_key(|(src, _)| src.start);
+
+    // Merge touching pieces that move by the same offset
+    let mut merged: IntervalMap = Vec::with_capacity(composed.len());
+    for (src, offset) in composed {
+        match merged.last_mut() {
+            Some((last, last_offset)) if last.end == src.start && *last_offset == offset => {
+                last.end = src.end;
+            }
+            _ => merged.push((src, offset)),
+        }
+    }
+    merged
+}
+
+// O(log n) point lookup in a sorted map
+fn lookup(map: &IntervalMap, value: u64) -> u64 {
+    let idx = map.partition_point(|(src, _)| src.end <= value);
+    match map.get(idx) {
+        Some(&(src, offset)) if src.start <= value => {
+            shift(value, offset).expect("rules are range checked while parsing")
+        }
+        _ => value,
+    }
+}
+
+// Every source interval that `map` sends into `target`
+fn preimage(map: &IntervalMap, target: &Interval) -> IntervalSet {
+    segments(map)
+        .into_iter()
+        .filter_map(|(src, offset)| {
+            (src + offset)
+                .overlap(target)
+                .map(|overlap| overlap + -offset)
+        })
+        .collect()
+}
+
+fn describe(map: &IntervalMap) -> String {
+    map.iter()
+        .map(|&(src, offset)| {
+            let dst = src + offset;
+            format!(
+                "[{}, {}) -> [{}, {}) ({offset:+})\n",
+                src.start, src.end, dst.start, dst.end
+            )
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum AlmanacError {
+    Syntax { line: usize, reason: String },
+    Overlap { map: String, lines: (usize, usize) },
+    Duplicate { map: String, lines: (usize, usize) },
+    ZeroLength { map: String, line: usize },
+    NoPath { from: String, to: String },
+    Overflow { line: usize, context: String },
+}
+
+impl fmt::Display for AlmanacError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax { line, reason } => write!(f, "line {line}: {reason}"),
+            Self::Overlap { map, lines } => write!(
+                f,
+                "{map} map: source ranges on lines {} and {} overlap",
+                lines.0, lines.1
+            ),
+            Self::Duplicate { map, lines } => write!(
+                f,
+                "{map} map: lines {} and {} are the same mapping",
+                lines.0, lines.1
+            ),
+            Self::ZeroLength { map, line } => {
+                write!(f, "{map} map: line {line} has a zero-length range")
+            }
+            Self::Overflow { line, context } => {
+                write!(f, "line {line} ({context}): range does not fit into u64")
+            }
+            Self::NoPath { from, to } => write!(f, "no chain of maps leads from {from} to {to}"),
+        }
+    }
+}
+
+// What to do when source ranges of a map overlap
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OverlapPolicy {
+    Error,
+    FirstWins, // the rule listed first keeps the overlapping part
+}
+
+#[derive(Debug)]
+struct Rule {
+    line: usize,
+    src: Interval,
+    offset: i128,
+}
+
+#[derive(Debug)]
+struct RawMap {
+    name: String, // "seed-to-soil"
+    source: String,
+    destination: String,
+    rules: Vec<Rule>,
+}
+
+impl RawMap {
+    fn issues(&self) -> Vec<AlmanacError> {
+        let mut issues = Vec::new();
+        let map = self.name.clone();
+        for (i, a) in self.rules.iter().enumerate() {
+            if a.src.is_empty() {
+                issues.push(AlmanacError::ZeroLength {
+                    map: map.clone(),
+                    line: a.line,
+                });
+            }
+            for b in &self.rules[i + 1..] {
+                let lines = (a.line, b.line);
+                if !a.src.is_empty() && a.src == b.src && a.offset == b.offset {
+                    issues.push(AlmanacError::Duplicate {
+                        map: map.clone(),
+                        lines,
+                    });
+                } else if a.src.overlap(&b.src).is_some() {
+                    issues.push(AlmanacError::Overlap {
+                        map: map.clone(),
+                        lines,
+                    });
+                }
+            }
+        }
+        issues
+    }
+
+    fn resolve(&self, policy: OverlapPolicy) -> Result<IntervalMap, AlmanacError> {
+        if policy == OverlapPolicy::Error {
+            let overlap = self.issues().into_iter().find(|issue| {
+                matches!(
+                    issue,
+                    AlmanacError::Overlap { .. } | AlmanacError::Duplicate { .. }
+                )
+            });
+            if let Some(issue) = overlap {
+                return Err(issue);
+            }
+        }
+
+        // Each rule only gets the parts not claimed by earlier rules
+        let mut map = IntervalMap::new();
+        let mut claimed = IntervalSet::new();
+        for rule in &self.rules {
+            let src: IntervalSet = [rule.src].into_iter().collect();
+            let unclaimed = src.difference(&claimed);
+            claimed = claimed.union(&unclaimed);
+            map.extend(unclaimed.into_iter().map(|piece| (piece, rule.offset)));
+        }
+        map.sort_by_key(|(src, _)| src.start);
+        Ok(map)
+    }
+}
+
 #[derive(Debug)]
 struct Almanac {
     seeds: Vec<Interval>,
     maps: Vec<IntervalMap>,
+    categories: Vec<(String, String)>, // (source, destination) of each map
 }
 
 impl Almanac {
-    fn parse(input: &str, as_intervals: bool) -> Self {
-        let mut lines = input.lines();
+    fn parse_raw(
+        input: &str,
+        as_intervals: bool,
+    ) -> Result<(Vec<Interval>, Vec<RawMap>), AlmanacError> {
+        let mut lines = input.lines().zip(1..);
 
         let raw_seeds = lines
             .next()
-            .unwrap()
-            .strip_prefix("seeds: ")
-            .unwrap()
+            .and_then(|(line, _)| line.strip_prefix("seeds: "))
+            .ok_or_else(|| AlmanacError::Syntax {
+                line: 1,
+                reason: "expected \"seeds: \"".into(),
+            })?
             .split_whitespace()
-            .map(|s| s.parse().unwrap());
+            .map(|s| {
+                s.parse::<u64>().map_err(|_| AlmanacError::Syntax {
+                    line: 1,
+                    reason: format!("invalid seed {s:?}"),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
 
+        if as_intervals && raw_seeds.len() % 2 != 0 {
+            return Err(AlmanacError::Syntax {
+                line: 1,
+                reason: "seed ranges need START LEN pairs".into(),
+            });
+        }
         let seeds = if as_intervals {
             raw_seeds
+                .into_iter()
                 .tuples()
-                .map(|(s, l)| Interval::new(s, s + l))
-                .collect_vec()
+                .map(|(s, l)| Interval::checked_new(s, l))
+                .collect::<Option<Vec<_>>>()
         } else {
-            raw_seeds.map(|s| Interval::new(s, s + 1)).collect_vec()
-        };
+            raw_seeds
+                .into_iter()
+                .map(|s| Interval::checked_new(s, 1))
+                .collect()
+        }
+        .ok_or_else(|| AlmanacError::Overflow {
+            line: 1,
+            context: "seeds".into(),
+        })?;
+        if seeds.iter().all(Interval::is_empty) {
+            return Err(AlmanacError::Syntax {
+                line: 1,
+                reason: "no seeds".into(),
+            });
+        }
 
         let mut maps = Vec::new();
-        for line in lines {
+        for (line, n) in lines {
             if line.is_empty() {
                 continue;
             }
-            if line.contains("map:") {
-                maps.push(IntervalMap::new());
+            let syntax = |reason: String| AlmanacError::Syntax { line: n, reason };
+            if let Some(name) = line.strip_suffix(" map:") {
+                let (source, destination) = name.split_once("-to-").ok_or_else(|| {
+                    syntax(format!("expected \"SOURCE-to-DESTINATION\", got {name:?}"))
+                })?;
+                maps.push(RawMap {
+                    name: name.to_owned(),
+                    source: source.to_owned(),
+                    destination: destination.to_owned(),
+                    rules: Vec::new(),
+                });
             } else {
-                let interval_map = line
+                let numbers = line
                     .split_whitespace()
-                    .map(|s| s.parse().unwrap())
-                    .tuples()
-                    .map(|(dst, src, len)| {
-                        (Interval::new(src, src + len), dst as i128 - src as i128)
+                    .map(|s| {
+                        s.parse::<u64>()
+                            .map_err(|_| syntax(format!("invalid number {s:?}")))
                     })
-                    .next()
-                    .unwrap();
+                    .collect::<Result<Vec<_>, _>>()?;
+                let &[dst, src, len] = &numbers[..] else {
+                    return Err(syntax("expected \"DST SRC LEN\"".into()));
+                };
+                let map = maps
+                    .last_mut()
+                    .ok_or_else(|| syntax("mapping outside of a map".into()))?;
+                let offset = dst as i128 - src as i128;
+                let src = Interval::checked_new(src, len)
+                    .filter(|src| src.translate(offset).is_some())
+                    .ok_or_else(|| AlmanacError::Overflow {
+                        line: n,
+                        context: format!("{} map", map.name),
+                    })?;
+                map.rules.push(Rule {
+                    line: n,
+                    src,
+                    offset,
+                });
+            }
+        }
+        Ok((seeds, maps))
+    }
+
+    fn parse(input: &str, as_intervals: bool, policy: OverlapPolicy) -> Result<Self, AlmanacError> {
+        let (seeds, raw_maps) = Self::parse_raw(input, as_intervals)?;
+        let maps = raw_maps
+            .iter()
+            .map(|map| map.resolve(policy))
+            .collect::<Result<_, _>>()?;
+        let categories = raw_maps
+            .into_iter()
+            .map(|map| (map.source, map.destination))
+            .collect();
+
+        Ok(Self {
+            seeds,
+            maps,
+            categories,
+        })
+    }
 
-                maps.last_mut().unwrap().push(interval_map);
+    // Indices of the maps leading from one category to another (breadth first,
+    // so the shortest chain wins if the graph branches)
+    fn path(&self, from: &str, to: &str) -> Result<Vec<usize>, AlmanacError> {
+        let mut previous: HashMap<&str, Option<usize>> = HashMap::from([(from, None)]);
+        let mut queue = VecDeque::from([from]);
+        while let Some(category) = queue.pop_front() {
+            if category == to {
+                let mut path = Vec::new();
+                let mut current = category;
+                while let Some(idx) = previous[current] {
+                    path.push(idx);
+                    current = &self.categories[idx].0;
+                }
+                path.reverse();
+                return Ok(path);
+            }
+            for (idx, (source, destination)) in self.categories.iter().enumerate() {
+                if source == category && !previous.contains_key(destination.as_str()) {
+                    previous.insert(destination, Some(idx));
+                    queue.push_back(destination);
+                }
             }
         }
+        Err(AlmanacError::NoPath {
+            from: from.to_owned(),
+            to: to.to_owned(),
+        })
+    }
 
-        // Assuming that the each map does not have overlapping regions
-        maps.iter_mut()
-            .for_each(|m| m.sort_by(|a, b| a.partial_cmp(b).unwrap()));
+    // Maps intervals of any category to any other (e.g. water to humidity)
+    fn map(
+        &self,
+        from: &str,
+        to: &str,
+        intervals: &IntervalSet,
+    ) -> Result<IntervalSet, AlmanacError> {
+        let mut intervals = intervals.clone();
+        for idx in self.path(from, to)? {
+            intervals = intervals
+                .iter()
+                .flat_map(|interval| Self::map_interval(interval, &self.maps[idx]))
+                .collect();
+        }
+        Ok(intervals)
+    }
 
-        Self { seeds, maps }
+    fn map_value(&self, from: &str, to: &str, value: u64) -> Result<u64, AlmanacError> {
+        let path = self.path(from, to)?;
+        Ok(path
+            .into_iter()
+            .fold(value, |value, idx| lookup(&self.maps[idx], value)))
+    }
+
+    // Every problem found in the maps (or the syntax error stopping the parse)
+    fn validate(input: &str) -> Vec<AlmanacError> {
+        match Self::parse_raw(input, false) {
+            Ok((_, maps)) => maps.iter().flat_map(RawMap::issues).collect(),
+            Err(err) => vec![err],
+        }
     }
 
     fn map_interval(interval: &Interval, map: &IntervalMap) -> Vec<Interval> {
@@ -131,43 +481,164 @@ impl Almanac {
         mapped
     }
 
-    fn all_mapped(&self) -> Vec<Interval> {
-        let mut intervals = self.seeds.clone();
-        for map in &self.maps {
-            let mut new_intervals = Vec::new();
-            for interval in intervals {
-                new_intervals.extend(Self::map_interval(&interval, map));
+    // Stage by stage mapping, kept as a reference for `composed`
+    fn all_mapped(&self) -> Result<IntervalSet, AlmanacError> {
+        self.map("seed", "location", &self.seeds.iter().copied().collect())
+    }
+
+    // A single seed-to-location map
+    fn composed(&self) -> Result<IntervalMap, AlmanacError> {
+        let chain = self.path("seed", "location")?;
+        Ok(chain.into_iter().fold(IntervalMap::new(), |acc, idx| {
+            compose(&acc, &self.maps[idx])
+        }))
+    }
+
+    // Only looks at the first seed of each interval; parsing made sure there is one
+    fn lowest_seed_location(&self) -> Result<u64, AlmanacError> {
+        let map = self.composed()?;
+        Ok(self
+            .seeds
+            .iter()
+            .map(|seed| lookup(&map, seed.start))
+            .min()
+            .unwrap())
+    }
+
+    // Parsing made sure at least one seed interval is not empty
+    fn lowest_location(&self) -> Result<u64, AlmanacError> {
+        let map = self.composed()?;
+        Ok(self
+            .seeds
+            .iter()
+            .flat_map(|seeds| Self::map_interval(seeds, &map))
+            .map(|r| r.start)
+            .min()
+            .unwrap())
+    }
+
+    // All seed intervals (whether listed in the almanac or not) that end up in `locations`
+    fn seeds_for(&self, locations: &Interval) -> Result<IntervalSet, AlmanacError> {
+        Ok(preimage(&self.composed()?, locations))
+    }
+
+    // Same as `lowest_location`, but searching backwards from location 0 upward
+    // in windows of doubling width
+    fn lowest_location_upward(&self) -> Result<Option<u64>, AlmanacError> {
+        let map = self.composed()?;
+        let seeds: IntervalSet = self.seeds.iter().copied().collect();
+        let (mut start, mut width) = (0u64, 1u64);
+        while start < u64::MAX {
+            let window = Interval::new(start, start.saturating_add(width));
+            let hits = preimage(&map, &window).intersection(&seeds);
+            let lowest = hits
+                .iter()
+                .flat_map(|seeds| Self::map_interval(seeds, &map))
+                .map(|r| r.start)
+                .min();
+            if lowest.is_some() {
+                return Ok(lowest);
             }
-            intervals = new_intervals;
+            start = window.end;
+            width = width.saturating_mul(2);
         }
-        intervals
+        Ok(None)
     }
 }
 
-fn part1(input: &str) -> u64 {
-    let almanac = Almanac::parse(input, false);
-    almanac
-        .all_mapped()
-        .into_iter()
-        .map(|r| r.start)
-        .min()
-        .unwrap()
+fn part1(input: &str, policy: OverlapPolicy) -> Result<u64, AlmanacError> {
+    Almanac::parse(input, false, policy)?.lowest_seed_location()
 }
 
-fn part2(input: &str) -> u64 {
-    let almanac = Almanac::parse(input, true);
-    almanac
-        .all_mapped()
-        .into_iter()
-        .map(|r| r.start)
-        .min()
-        .unwrap()
+fn part2(input: &str, policy: OverlapPolicy) -> Result<u64, AlmanacError> {
+    Almanac::parse(input, true, policy)?.lowest_location()
+}
+
+const USAGE: &str = "\
+usage: day-05 [--first-wins] [--composed | --seeds-for START..END | --verify]
+       day-05 [--first-wins] --map FROM TO VALUE|START..END
+       day-05 --validate";
+
+fn parse_range(spec: &str) -> Option<Interval> {
+    let (start, end) = spec.split_once("..")?;
+    Some(Interval::new(start.parse().ok()?, end.parse().ok()?))
+}
+
+fn run(text: &str, args: &[&str]) -> Result<(), AlmanacError> {
+    let (policy, args) = match args {
+        ["--first-wins", rest @ ..] => (OverlapPolicy::FirstWins, rest),
+        _ => (OverlapPolicy::Error, args),
+    };
+    let parse = |as_intervals| Almanac::parse(text, as_intervals, policy);
+
+    match args {
+        [] => {
+            println!("Part 1: {}", part1(text, policy)?);
+            println!("Part 2: {}", part2(text, policy)?);
+        }
+        ["--composed"] => print!("{}", describe(&parse(false)?.composed()?)),
+        ["--seeds-for", range] => match parse_range(range) {
+            Some(locations) => {
+                for seeds in parse(false)?.seeds_for(&locations)? {
+                    println!("[{}, {})", seeds.start, seeds.end);
+                }
+            }
+            None => {
+                eprintln!("invalid location range {range:?}, expected START..END");
+                std::process::exit(1);
+            }
+        },
+        ["--map", from, to, what] => {
+            let almanac = parse(false)?;
+            if let Ok(value) = what.parse() {
+                println!("{}", almanac.map_value(from, to, value)?);
+            } else if let Some(interval) = parse_range(what) {
+                let intervals = [interval].into_iter().collect();
+                for mapped in almanac.map(from, to, &intervals)? {
+                    println!("[{}, {})", mapped.start, mapped.end);
+                }
+            } else {
+                eprintln!("invalid value or range {what:?}");
+                std::process::exit(1);
+            }
+        }
+        ["--verify"] => {
+            let almanac = parse(true)?;
+            let forward = almanac.lowest_location()?;
+            let upward = almanac.lowest_location_upward()?;
+            let staged = almanac.all_mapped()?;
+            assert_eq!(Some(forward), upward);
+            assert_eq!(Some(forward), staged.min());
+            println!("Part 2 (composed): {forward}");
+            println!("Part 2 (upward):   {forward}");
+            println!("Part 2 (staged):   {forward} ({} fragments)", staged.len());
+        }
+        ["--validate"] if policy == OverlapPolicy::Error => {
+            let issues = Almanac::validate(text);
+            for issue in &issues {
+                println!("{issue}");
+            }
+            if issues.is_empty() {
+                println!("No issues found");
+            }
+        }
+        _ => {
+            eprintln!("{USAGE}");
+            std::process::exit(1);
+        }
+    }
+    Ok(())
 }
 
 fn main() {
     let text = include_str!("../inputs/input.txt");
-    println!("Part 1: {}", part1(text));
-    println!("Part 2: {}", part2(text));
+
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    let args: Vec<_> = args.iter().map(String::as_str).collect();
+    if let Err(err) = run(text, &args) {
+        eprintln!("Error: {err}");
+        std::process::exit(1);
+    }
 }
 
 #[cfg(test)]
@@ -211,11 +682,277 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1(INPUT), 35);
+        assert_eq!(part1(INPUT, OverlapPolicy::Error), Ok(35));
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2(INPUT), 46);
+        assert_eq!(part2(INPUT, OverlapPolicy::Error), Ok(46));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(Almanac::validate(INPUT), vec![]);
+
+        let input = "\
+            seeds: 1 5 10 20\n\
+            \n\
+            seed-to-soil map:\n\
+            100 0 10\n\
+            200 5 10\n\
+            100 0 10\n\
+            300 50 0\n\
+            \n\
+            soil-to-location map:\n\
+            0 1000 1";
+        let map = || "seed-to-soil".to_owned();
+        assert_eq!(
+            Almanac::validate(input),
+            vec![
+                AlmanacError::Overlap {
+                    map: map(),
+                    lines: (4, 5)
+                },
+                AlmanacError::Duplicate {
+                    map: map(),
+                    lines: (4, 6)
+                },
+                AlmanacError::Overlap {
+                    map: map(),
+                    lines: (5, 6)
+                },
+                AlmanacError::ZeroLength {
+                    map: map(),
+                    line: 7
+                },
+            ]
+        );
+
+        assert_eq!(
+            Almanac::parse(input, false, OverlapPolicy::Error).unwrap_err(),
+            AlmanacError::Overlap {
+                map: map(),
+                lines: (4, 5)
+            }
+        );
+        // 0..10 comes from the first rule, only 10..15 is left for the second
+        let almanac = Almanac::parse(input, false, OverlapPolicy::FirstWins).unwrap();
+        assert_eq!(
+            describe(&almanac.maps[0]),
+            "[0, 10) -> [100, 110) (+100)\n[10, 15) -> [205, 210) (+195)\n"
+        );
+        assert_eq!(
+            part1(input, OverlapPolicy::Error),
+            Err(Almanac::parse(input, false, OverlapPolicy::Error).unwrap_err())
+        );
+        assert_eq!(part1(input, OverlapPolicy::FirstWins), Ok(20));
+
+        assert!(matches!(
+            Almanac::validate("seeds: 1 x")[..],
+            [AlmanacError::Syntax { line: 1, .. }]
+        ));
+        let no_seeds = || AlmanacError::Syntax {
+            line: 1,
+            reason: "no seeds".into(),
+        };
+        let input = "seeds: \n\nseed-to-location map:\n1 2 3";
+        assert_eq!(part1(input, OverlapPolicy::Error), Err(no_seeds()));
+        let input = "seeds: 1 2 3\n\nseed-to-location map:\n1 2 3";
+        assert_eq!(part1(input, OverlapPolicy::Error), Ok(1));
+        assert_eq!(
+            part2(input, OverlapPolicy::Error),
+            Err(AlmanacError::Syntax {
+                line: 1,
+                reason: "seed ranges need START LEN pairs".into()
+            })
+        );
+        let input = "seeds: 5 0\n\nseed-to-location map:\n1 2 3";
+        assert_eq!(part2(input, OverlapPolicy::Error), Err(no_seeds()));
+        assert!(matches!(
+            Almanac::validate("seeds: 1\n\nx-to-y map:\n1 2")[..],
+            [AlmanacError::Syntax { line: 4, .. }]
+        ));
+    }
+
+    #[test]
+    fn test_overflow() {
+        let parse = |input: &str| Almanac::parse(input, true, OverlapPolicy::Error).map(|_| ());
+        let overflow = |line, context: &str| {
+            Err(AlmanacError::Overflow {
+                line,
+                context: context.into(),
+            })
+        };
+        let max = u64::MAX;
+
+        assert_eq!(parse(&format!("seeds: {max} 1")), overflow(1, "seeds"));
+        assert_eq!(
+            Almanac::parse(&format!("seeds: {max}"), false, OverlapPolicy::Error).map(|_| ()),
+            overflow(1, "seeds")
+        );
+
+        let with_rule =
+            |rule: String| format!("seeds: 0 1\n\nseed-to-location map:\n0 1 1\n{rule}");
+        assert_eq!(parse(&with_rule(format!("0 {} 1", max - 1))), Ok(()));
+        assert_eq!(
+            parse(&with_rule(format!("0 {max} 2"))),
+            overflow(5, "seed-to-location map")
+        );
+        assert_eq!(
+            parse(&with_rule(format!("{max} 5 2"))),
+            overflow(5, "seed-to-location map")
+        );
+        assert_eq!(parse(&with_rule(format!("{} 5 2", max - 2))), Ok(()));
+
+        assert_eq!(Interval::new(5, 10).translate(-6), None);
+        assert_eq!(
+            Interval::new(5, 10).translate(-5),
+            Some(Interval::new(0, 5))
+        );
+        assert_eq!(
+            Interval::new(5, 10).translate((max - 10).into()),
+            Some(Interval::new(max - 5, max))
+        );
+        assert_eq!(Interval::new(5, 10).translate((max - 9).into()), None);
+    }
+
+    #[test]
+    fn test_categories() {
+        let almanac = Almanac::parse(INPUT, false, OverlapPolicy::Error).unwrap();
+        assert_eq!(almanac.map_value("seed", "soil", 79), Ok(81));
+        assert_eq!(almanac.map_value("seed", "location", 13), Ok(35));
+        assert_eq!(almanac.map_value("fertilizer", "fertilizer", 7), Ok(7));
+        for value in 0..120 {
+            let staged = almanac.maps[2..=5]
+                .iter()
+                .fold(value, |value, map| lookup(map, value));
+            assert_eq!(
+                almanac.map_value("fertilizer", "humidity", value),
+                Ok(staged)
+            );
+        }
+        let water: IntervalSet = [Interval::new(0, 100)].into_iter().collect();
+        let humidity = almanac.map("water", "humidity", &water).unwrap();
+        assert_eq!(humidity.iter().map(|r| r.end - r.start).sum::<u64>(), 100);
+
+        assert_eq!(
+            almanac.map_value("location", "seed", 1),
+            Err(AlmanacError::NoPath {
+                from: "location".into(),
+                to: "seed".into()
+            })
+        );
+
+        // Maps in reverse order plus a dead-end branch
+        let mut blocks: Vec<_> = INPUT.split("\n\n").collect();
+        blocks[1..].reverse();
+        blocks.insert(3, "soil-to-nowhere map:\n1 2 3");
+        let shuffled = blocks.join("\n\n");
+        assert_eq!(part1(&shuffled, OverlapPolicy::Error), Ok(35));
+        assert_eq!(part2(&shuffled, OverlapPolicy::Error), Ok(46));
+        let almanac = Almanac::parse(&shuffled, false, OverlapPolicy::Error).unwrap();
+        assert_eq!(almanac.map_value("seed", "nowhere", 50), Ok(52));
+
+        // No way to the locations: only the parts need one
+        let broken = INPUT.replace("water-to-light", "water-to-lamp");
+        assert_eq!(
+            part1(&broken, OverlapPolicy::Error),
+            Err(AlmanacError::NoPath {
+                from: "seed".into(),
+                to: "location".into()
+            })
+        );
+        let almanac = Almanac::parse(&broken, false, OverlapPolicy::Error).unwrap();
+        assert_eq!(almanac.map_value("seed", "water", 79), Ok(81));
+        assert!(almanac.composed().is_err());
+    }
+
+    #[test]
+    fn test_seeds_for() {
+        let almanac = Almanac::parse(INPUT, true, OverlapPolicy::Error).unwrap();
+        let map = almanac.composed().unwrap();
+
+        // Locations below 200 only come from seeds below 200
+        for (a, b) in [(0, 1), (46, 47), (35, 60), (0, 120), (99, 150)] {
+            let seeds = almanac.seeds_for(&Interval::new(a, b)).unwrap();
+            let from_preimage: Vec<u64> =
+                seeds.iter().flat_map(|r| r.start..r.end.min(200)).collect();
+            let brute_force: Vec<u64> = (0..200)
+                .filter(|&s| (a..b).contains(&lookup(&map, s)))
+                .collect();
+            assert_eq!(from_preimage, brute_force);
+        }
+        assert!(almanac
+            .seeds_for(&Interval::new(46, 47))
+            .unwrap()
+            .iter()
+            .any(|r| r.start <= 82 && 82 < r.end));
+
+        assert_eq!(almanac.lowest_location_upward().unwrap(), Some(46));
+        assert_eq!(
+            Almanac::parse(INPUT, false, OverlapPolicy::Error)
+                .unwrap()
+                .lowest_location_upward()
+                .unwrap(),
+            Some(35)
+        );
+    }
+
+    #[test]
+    fn test_fragments_bounded() {
+        // Every stage rotates [0, 1000) by one, which cuts each interval crossing
+        // 999 in two. Without merging we would end up with 51 fragments.
+        let mut input = String::from("seeds: 0 1000\n");
+        for stage in 0..50 {
+            let from = if stage == 0 {
+                "seed".into()
+            } else {
+                format!("stage{stage}")
+            };
+            let to = if stage == 49 {
+                "location".into()
+            } else {
+                format!("stage{}", stage + 1)
+            };
+            input += &format!("\n{from}-to-{to} map:\n1 0 999\n0 999 1\n");
+        }
+        let almanac = Almanac::parse(&input, true, OverlapPolicy::Error).unwrap();
+        let mapped = almanac.all_mapped().unwrap();
+        assert_eq!(mapped.len(), 1);
+        assert_eq!(mapped.min(), Some(0));
+
+        let almanac = Almanac::parse(INPUT, true, OverlapPolicy::Error).unwrap();
+        assert!(almanac.all_mapped().unwrap().len() <= 4);
+    }
+
+    #[test]
+    fn test_composed() {
+        let almanac = Almanac::parse(INPUT, false, OverlapPolicy::Error).unwrap();
+        let map = almanac.composed().unwrap();
+        for seed in 0..120 {
+            let staged = almanac
+                .maps
+                .iter()
+                .fold(seed, |value, stage| lookup(stage, value));
+            assert_eq!(lookup(&map, seed), staged);
+        }
+
+        let almanac = Almanac::parse(INPUT, true, OverlapPolicy::Error).unwrap();
+        let staged = almanac.all_mapped().unwrap();
+        let composed: IntervalSet = almanac
+            .seeds
+            .iter()
+            .flat_map(|seeds| Almanac::map_interval(seeds, &map))
+            .collect();
+        assert_eq!(composed, staged);
+
+        assert_eq!(
+            compose(&IntervalMap::new(), &IntervalMap::new()),
+            IntervalMap::new()
+        );
+        assert_eq!(
+            describe(&compose(&almanac.maps[0], &IntervalMap::new())),
+            "[50, 98) -> [52, 100) (+2)\n[98, 100) -> [50, 52) (-48)\n"
+        );
     }
 }
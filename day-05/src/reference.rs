@@ -0,0 +1,89 @@
+// Brute-force reference solver and property tests for the interval machinery
+
+use itertools::Itertools;
+use proptest::prelude::*;
+
+use super::{Almanac, Interval, IntervalSet, OverlapPolicy};
+
+// (dst, src, len) as written in the almanac
+type Rule = (u64, u64, u64);
+
+#[derive(Debug, Clone)]
+struct Spec {
+    seeds: Vec<(u64, u64)>, // (start, len)
+    maps: Vec<Vec<Rule>>,
+}
+
+impl Spec {
+    fn to_input(&self) -> String {
+        let seeds = self.seeds.iter().map(|(s, l)| format!("{s} {l}")).join(" ");
+        let mut input = format!("seeds: {seeds}\n");
+        for (i, rules) in self.maps.iter().enumerate() {
+            let from = if i == 0 {
+                "seed".into()
+            } else {
+                format!("c{i}")
+            };
+            let to = if i + 1 == self.maps.len() {
+                "location".into()
+            } else {
+                format!("c{}", i + 1)
+            };
+            input += &format!("\n{from}-to-{to} map:\n");
+            for (dst, src, len) in rules {
+                input += &format!("{dst} {src} {len}\n");
+            }
+        }
+        input
+    }
+
+    // One seed at a time; the first matching rule wins, just like OverlapPolicy::FirstWins
+    fn locations(&self) -> Vec<u64> {
+        self.seeds
+            .iter()
+            .flat_map(|&(start, len)| start..start + len)
+            .map(|seed| {
+                self.maps.iter().fold(seed, |value, rules| {
+                    rules
+                        .iter()
+                        .find(|&&(_, src, len)| src <= value && value < src + len)
+                        .map_or(value, |&(dst, src, _)| value - src + dst)
+                })
+            })
+            .collect()
+    }
+}
+
+fn spec() -> impl Strategy<Value = Spec> {
+    let rule = (0..60u64, 0..60u64, 0..12u64);
+    let seeds = prop::collection::vec((0..60u64, 1..10u64), 1..4);
+    let maps = prop::collection::vec(prop::collection::vec(rule, 0..5), 1..5);
+    (seeds, maps).prop_map(|(seeds, maps)| Spec { seeds, maps })
+}
+
+proptest! {
+    #[test]
+    fn all_mapped_matches_brute_force(spec in spec()) {
+        let almanac = Almanac::parse(&spec.to_input(), true, OverlapPolicy::FirstWins).unwrap();
+        let expected: IntervalSet = spec
+            .locations()
+            .into_iter()
+            .map(|location| Interval::new(location, location + 1))
+            .collect();
+        let lowest = expected.min();
+
+        prop_assert_eq!(almanac.all_mapped().unwrap(), expected);
+        prop_assert_eq!(almanac.lowest_location().ok(), lowest);
+        prop_assert_eq!(almanac.lowest_location_upward().unwrap(), lowest);
+    }
+
+    #[test]
+    fn unit_seeds_match_brute_force(spec in spec()) {
+        let almanac = Almanac::parse(&spec.to_input(), false, OverlapPolicy::FirstWins).unwrap();
+        let unit = Spec {
+            seeds: spec.seeds.iter().flat_map(|&(s, l)| [(s, 1), (l, 1)]).collect(),
+            maps: spec.maps.clone(),
+        };
+        prop_assert_eq!(almanac.lowest_seed_location().ok(), unit.locations().into_iter().min());
+    }
+}
@@ -1,65 +1,197 @@
-fn parse1(input: &str) -> Vec<(u64, u64)> {
-    let mut lines = input.lines();
-
-    let mut get_items = |prefix| {
-        lines
-            .next()
-            .unwrap()
-            .strip_prefix(prefix)
-            .unwrap()
-            .split_whitespace()
-            .map(|s| s.parse().unwrap())
-    };
+use std::fmt;
+use std::num::IntErrorKind;
+
+use physics::Physics;
+
+mod physics;
+
+#[derive(Debug, Clone, PartialEq)]
+enum SheetError {
+    Syntax { line: usize, reason: String },
+    Missing(&'static str),
+    ColumnMismatch { times: usize, distances: usize },
+}
 
-    get_items("Time: ").zip(get_items("Distance: ")).collect()
+impl fmt::Display for SheetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax { line, reason } => write!(f, "line {line}: {reason}"),
+            Self::Missing(row) => write!(f, "no {row} row"),
+            Self::ColumnMismatch { times, distances } => {
+                write!(f, "{times} times but {distances} distances")
+            }
+        }
+    }
+}
+
+// Both readings of the sheet
+#[derive(Debug, Clone, PartialEq)]
+struct Sheet {
+    races: Vec<(u64, u64)>, // (time, distance) per column
+    // All digits of a row making up a single number, only part 2 needs it to fit
+    kerned: Result<(u64, u64), SheetError>,
 }
 
-fn parse2(input: &str) -> (u64, u64) {
-    let mut lines = input.lines();
-
-    let mut get_item = |prefix| {
-        lines
-            .next()
-            .unwrap()
-            .strip_prefix(prefix)
-            .unwrap()
-            .chars()
-            .filter(char::is_ascii_digit)
+// Accepts the two rows in any order, with any whitespace and any number of races
+fn parse(input: &str) -> Result<Sheet, SheetError> {
+    let mut times = None;
+    let mut distances = None;
+
+    for (i, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let syntax = |reason: String| SheetError::Syntax {
+            line: i + 1,
+            reason,
+        };
+
+        let (label, values) = line
+            .split_once(':')
+            .ok_or_else(|| syntax("expected \"Time:\" or \"Distance:\"".into()))?;
+        let row = match label.trim() {
+            "Time" => &mut times,
+            "Distance" => &mut distances,
+            label => return Err(syntax(format!("unknown row {label:?}"))),
+        };
+        if row.is_some() {
+            return Err(syntax(format!("second {} row", label.trim())));
+        }
+
+        let numbers = values
+            .split_whitespace()
+            .map(|s| {
+                s.parse::<u64>()
+                    .map_err(|_| syntax(format!("invalid number {s:?}")))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let kerned = values
+            .split_whitespace()
             .collect::<String>()
             .parse::<u64>()
-            .unwrap()
-    };
+            .map_err(|err| match err.kind() {
+                IntErrorKind::Empty => syntax("no digits to kern".into()),
+                _ => syntax("kerned number does not fit into u64".into()),
+            });
+        *row = Some((numbers, kerned));
+    }
+
+    let (times, kerned_time) = times.ok_or(SheetError::Missing("Time"))?;
+    let (distances, kerned_distance) = distances.ok_or(SheetError::Missing("Distance"))?;
+    if times.len() != distances.len() {
+        return Err(SheetError::ColumnMismatch {
+            times: times.len(),
+            distances: distances.len(),
+        });
+    }
 
-    (get_item("Time: "), get_item("Distance: "))
+    Ok(Sheet {
+        races: times.into_iter().zip(distances).collect(),
+        kerned: kerned_time.and_then(|t| Ok((t, kerned_distance?))),
+    })
 }
 
-fn margin(time: u64, distance: u64) -> u64 {
-    let d_roots = (((time * time) - 4 * distance) as f64).sqrt();
-    let lo = (time as f64 - d_roots) / 2.0 * (1.0 + f64::EPSILON);
-    let hi = (time as f64 + d_roots) / 2.0 * (1.0 - f64::EPSILON);
-    if d_roots > 0.0 {
-        (hi.floor() - lo.ceil() + 1.0) as u64
-    } else {
-        0
+// Inclusive range of hold times h that beat the record: h * (time - h) > distance
+fn winning_range(time: u64, distance: u64) -> Option<(u64, u64)> {
+    let beats = |h: u64| h as u128 * (time - h) as u128 > distance as u128;
+
+    // Holding for half of the time goes the farthest
+    if !beats(time / 2) {
+        return None;
+    }
+
+    // The roots are (time ± sqrt(time² - 4 * distance)) / 2, the integer square
+    // root gets us within one of the lower bound
+    let (t, d) = (time as u128, distance as u128);
+    let s = (t * t - 4 * d).isqrt() as u64;
+    let mut lo = (time - s) / 2;
+    while !beats(lo) {
+        lo += 1;
+    }
+    while lo > 0 && beats(lo - 1) {
+        lo -= 1;
     }
+    Some((lo, time - lo))
+}
+
+fn margin(time: u64, distance: u64) -> u64 {
+    winning_range(time, distance).map_or(0, |(lo, hi)| hi - lo + 1)
 }
 
-fn part1(input: &str) -> u64 {
-    parse1(input)
+fn part1(input: &str) -> Result<u64, SheetError> {
+    Ok(parse(input)?
+        .races
         .into_iter()
         .map(|(t, d)| margin(t, d))
-        .product()
+        .product())
+}
+
+fn part2(input: &str) -> Result<u64, SheetError> {
+    let (time, distance) = parse(input)?.kerned?;
+    Ok(margin(time, distance))
 }
 
-fn part2(input: &str) -> u64 {
-    let (time, distance) = parse2(input);
-    margin(time, distance)
+fn parse_physics(args: &[String]) -> Result<Physics, String> {
+    let mut physics = Physics::default();
+    for pair in args.chunks(2) {
+        let value = || {
+            pair.get(1)
+                .and_then(|v| v.parse().ok())
+                .ok_or(format!("{} needs a number", pair[0]))
+        };
+        match pair[0].as_str() {
+            "--acceleration" => physics.acceleration = value()?,
+            "--max-speed" => physics.max_speed = Some(value()?),
+            "--friction" => physics.friction = value()?,
+            arg => return Err(format!("unknown argument {arg:?}")),
+        }
+    }
+    Ok(physics)
 }
 
 fn main() {
     let text = include_str!("../inputs/input.txt");
-    println!("Part 1: {}", part1(text));
-    println!("Part 2: {}", part2(text));
+
+    let sheet = match parse(text) {
+        Ok(sheet) => sheet,
+        Err(err) => {
+            eprintln!("Error: {err}");
+            std::process::exit(1);
+        }
+    };
+
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    if args.is_empty() {
+        // parse() already succeeded, so part 1 can't fail
+        println!("Part 1: {}", part1(text).unwrap());
+        match part2(text) {
+            Ok(answer) => println!("Part 2: {answer}"),
+            Err(err) => {
+                eprintln!("Error: {err}");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let physics = match parse_physics(&args) {
+        Ok(physics) => physics,
+        Err(err) => {
+            eprintln!("{err}");
+            eprintln!("usage: day-06 [--acceleration N] [--max-speed N] [--friction N]");
+            std::process::exit(1);
+        }
+    };
+    for (time, distance) in sheet.races.into_iter().chain(sheet.kerned) {
+        let solution = physics.solve(time, distance);
+        println!(
+            "time {time}, record {distance}: {} ways to win {:?}, best hold {} ms goes {}",
+            solution.margin(),
+            solution.winning,
+            solution.optimal,
+            solution.best_distance
+        );
+    }
 }
 
 #[cfg(test)]
@@ -72,11 +204,100 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1(INPUT), 288);
+        assert_eq!(part1(INPUT), Ok(288));
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2(INPUT), 71503);
+        assert_eq!(part2(INPUT), Ok(71503));
+    }
+
+    #[test]
+    fn test_parse() {
+        let expected = Sheet {
+            races: vec![(7, 9), (15, 40), (30, 200)],
+            kerned: Ok((71530, 940200)),
+        };
+        assert_eq!(parse(INPUT), Ok(expected.clone()));
+        assert_eq!(
+            parse("\n  Distance:9 40\t 200  \n\nTime :  7 15 30\n"),
+            Ok(expected)
+        );
+        assert_eq!(
+            parse("Time: 1 2 3 4 5\nDistance: 0 0 0 0 0").map(|s| s.races.len()),
+            Ok(5)
+        );
+
+        assert_eq!(
+            parse("Time: 7 15 30\nDistance: 9 40"),
+            Err(SheetError::ColumnMismatch {
+                times: 3,
+                distances: 2
+            })
+        );
+        assert_eq!(part1("Time: 7 15"), Err(SheetError::Missing("Distance")));
+        let syntax_line = |input| match parse(input) {
+            Err(SheetError::Syntax { line, .. }) => Some(line),
+            _ => None,
+        };
+        assert_eq!(syntax_line("Time: 7\nSpeed: 9"), Some(2));
+        assert_eq!(syntax_line("Time: 7\nTime: 9"), Some(2));
+        assert_eq!(syntax_line("Time: 7x\nDistance: 9"), Some(1));
+
+        // Kerning only matters for part 2
+        let huge = "Time: 7 7\nDistance: 99999999999 99999999999";
+        assert_eq!(part1(huge), Ok(0));
+        assert_eq!(
+            part2(huge),
+            Err(SheetError::Syntax {
+                line: 2,
+                reason: "kerned number does not fit into u64".into()
+            })
+        );
+        assert_eq!(part1("Time:\nDistance:"), Ok(1));
+        assert_eq!(
+            part2("Time:\nDistance:"),
+            Err(SheetError::Syntax {
+                line: 1,
+                reason: "no digits to kern".into()
+            })
+        );
+    }
+
+    #[test]
+    fn test_winning_range() {
+        for time in 0..60 {
+            for distance in 0..(time * time / 4 + 2) {
+                let wins: Vec<_> = (0..=time).filter(|h| h * (time - h) > distance).collect();
+                let expected = wins.first().map(|&lo| (lo, *wins.last().unwrap()));
+                assert_eq!(winning_range(time, distance), expected, "{time} {distance}");
+            }
+        }
+
+        // Perfect square discriminants: the roots are the integers 0 and time
+        assert_eq!(winning_range(7, 0), Some((1, 6)));
+        assert_eq!(margin(30, 0), 29);
+        // Roots 10 and 20 are exactly at the record, so they don't count
+        assert_eq!(winning_range(30, 200), Some((11, 19)));
+        // Best case only ties the record
+        assert_eq!(winning_range(8, 16), None);
+        assert_eq!(winning_range(8, 1000), None);
+    }
+
+    #[test]
+    fn test_winning_range_large() {
+        let max = u64::MAX;
+        assert_eq!(winning_range(max, 0), Some((1, max - 1)));
+        assert_eq!(winning_range(max, max), Some((2, max - 2)));
+        // Roots 1 and max - 1
+        assert_eq!(winning_range(max, max - 1), Some((2, max - 2)));
+        // Roots 2^32 - 1 and 2^32 + 1
+        assert_eq!(winning_range(1 << 33, max), Some((1 << 32, 1 << 32)));
+        // Roots 2^32 - 2 and 2^32 + 2
+        assert_eq!(
+            winning_range(1 << 33, max - 3),
+            Some(((1 << 32) - 1, (1 << 32) + 1))
+        );
+        assert_eq!(winning_range(1 << 32, max), None);
     }
 }
@@ -0,0 +1,227 @@
+use super::winning_range;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Physics {
+    pub acceleration: u64,      // speed gained per ms of holding the button
+    pub max_speed: Option<u64>, // the boat never goes faster than this
+    pub friction: u64,          // speed lost per ms of moving (0 = none)
+}
+
+impl Default for Physics {
+    // The puzzle's rules
+    fn default() -> Self {
+        Self {
+            acceleration: 1,
+            max_speed: None,
+            friction: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Solution {
+    pub winning: Vec<(u64, u64)>, // inclusive ranges of hold times beating the record
+    pub optimal: u64,             // (shortest) hold time going the farthest
+    pub best_distance: u128,
+}
+
+impl Solution {
+    pub fn margin(&self) -> u64 {
+        self.winning.iter().map(|(lo, hi)| hi - lo + 1).sum()
+    }
+}
+
+impl Physics {
+    fn speed(&self, hold: u64) -> u64 {
+        let speed = hold.saturating_mul(self.acceleration);
+        self.max_speed.map_or(speed, |max| speed.min(max))
+    }
+
+    // In every ms the boat moves by its current speed, then slows down by `friction`
+    pub fn distance(&self, time: u64, hold: u64) -> u128 {
+        let speed = self.speed(hold) as u128;
+        let moving = (time - hold) as u128;
+        if self.friction == 0 {
+            return speed * moving;
+        }
+        let f = self.friction as u128;
+        let steps = moving.min(speed.div_ceil(f));
+        steps * speed - f * steps * steps.saturating_sub(1) / 2
+    }
+
+    pub fn solve(&self, time: u64, distance: u64) -> Solution {
+        if self.friction == 0 {
+            self.solve_closed_form(time, distance)
+        } else {
+            self.solve_by_search(time, distance)
+        }
+    }
+
+    // Without friction: acceleration * h * (time - h), capped at max_speed * (time - h).
+    // Both are concave, so their minimum is too: there is a single winning range
+    // and the optimum is either at time / 2 or where the cap kicks in. Speeds
+    // saturate at u64::MAX, so without a max_speed that is the cap.
+    fn solve_closed_form(&self, time: u64, distance: u64) -> Solution {
+        // a * x > d <=> x > d / a (integer division)
+        let mut winning = distance
+            .checked_div(self.acceleration)
+            .and_then(|d| winning_range(time, d));
+
+        let mut candidates = vec![time / 2, time.div_ceil(2)];
+        let max = self.max_speed.unwrap_or(u64::MAX);
+        // max * (time - h) > d <=> h < time - d / max
+        let last = distance
+            .checked_div(max)
+            .and_then(|d| (time - d.min(time)).checked_sub(1));
+        winning = match (winning, last) {
+            (Some((lo, hi)), Some(last)) if lo <= last => Some((lo, hi.min(last))),
+            _ => None,
+        };
+        if let Some(kink) = max.checked_div(self.acceleration) {
+            candidates.extend([kink.min(time), kink.saturating_add(1).min(time)]);
+        }
+
+        let (optimal, best_distance) = self.best_of(time, candidates);
+        Solution {
+            winning: winning.into_iter().collect(),
+            optimal,
+            best_distance,
+        }
+    }
+
+    // Tries every hold time
+    fn solve_by_search(&self, time: u64, distance: u64) -> Solution {
+        let mut winning: Vec<(u64, u64)> = Vec::new();
+        for hold in 0..=time {
+            if self.distance(time, hold) > distance as u128 {
+                match winning.last_mut() {
+                    Some((_, hi)) if *hi + 1 == hold => *hi = hold,
+                    _ => winning.push((hold, hold)),
+                }
+            }
+        }
+        let (optimal, best_distance) = self.best_of(time, 0..=time);
+        Solution {
+            winning,
+            optimal,
+            best_distance,
+        }
+    }
+
+    fn best_of(&self, time: u64, holds: impl IntoIterator<Item = u64>) -> (u64, u128) {
+        holds
+            .into_iter()
+            .map(|hold| (hold, self.distance(time, hold)))
+            .fold((0, 0), |best, (hold, d)| {
+                if d > best.1 || (d == best.1 && hold < best.0) {
+                    (hold, d)
+                } else {
+                    best
+                }
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn models() -> Vec<Physics> {
+        let mut models = Vec::new();
+        for acceleration in 0..4 {
+            for max_speed in [None, Some(0), Some(1), Some(5), Some(12)] {
+                for friction in 0..3 {
+                    models.push(Physics {
+                        acceleration,
+                        max_speed,
+                        friction,
+                    });
+                }
+            }
+        }
+        models
+    }
+
+    #[test]
+    fn test_default_model() {
+        let physics = Physics::default();
+        for (time, distance) in [(7, 9), (15, 40), (30, 200), (71530, 940200)] {
+            let solution = physics.solve(time, distance);
+            assert_eq!(
+                solution.winning,
+                winning_range(time, distance)
+                    .into_iter()
+                    .collect::<Vec<_>>()
+            );
+            assert_eq!(solution.optimal, time / 2);
+        }
+        assert_eq!(physics.solve(30, 200).margin(), 9);
+    }
+
+    #[test]
+    fn test_closed_form_matches_search() {
+        for physics in models().into_iter().filter(|p| p.friction == 0) {
+            for time in 0..40 {
+                for distance in [0, 1, 7, 30, 100, 250, u64::MAX] {
+                    assert_eq!(
+                        physics.solve_closed_form(time, distance),
+                        physics.solve_by_search(time, distance),
+                        "{physics:?} {time} {distance}"
+                    );
+                }
+            }
+        }
+
+        // Speeds saturating at u64::MAX cap the distance like max_speed does
+        for acceleration in [u64::MAX / 3, u64::MAX] {
+            let physics = Physics {
+                acceleration,
+                ..Physics::default()
+            };
+            for time in 0..40 {
+                for distance in [0, 1, u64::MAX - 1, u64::MAX] {
+                    assert_eq!(
+                        physics.solve_closed_form(time, distance),
+                        physics.solve_by_search(time, distance),
+                        "{physics:?} {time} {distance}"
+                    );
+                }
+            }
+        }
+        let physics = Physics {
+            acceleration: u64::MAX,
+            ..Physics::default()
+        };
+        assert_eq!(physics.solve(10, 1).optimal, 1);
+    }
+
+    #[test]
+    fn test_friction() {
+        let physics = Physics {
+            acceleration: 2,
+            max_speed: None,
+            friction: 1,
+        };
+        // Holding 3 ms gives speed 6 for the remaining 7 ms: 6+5+4+3+2+1 = 21
+        assert_eq!(physics.distance(10, 3), 21);
+        // Holding 6 ms gives speed 12 for 4 ms: 12+11+10+9 = 42
+        assert_eq!(physics.distance(10, 6), 42);
+
+        for physics in models() {
+            for time in 0..25 {
+                let brute_force = |hold: u64| {
+                    let mut speed = physics.speed(hold);
+                    let mut travelled = 0;
+                    for _ in hold..time {
+                        travelled += speed as u128;
+                        speed = speed.saturating_sub(physics.friction);
+                    }
+                    travelled
+                };
+                for hold in 0..=time {
+                    assert_eq!(physics.distance(time, hold), brute_force(hold));
+                }
+            }
+        }
+    }
+}
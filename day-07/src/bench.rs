@@ -0,0 +1,64 @@
+use std::time::Instant;
+
+use itertools::Itertools;
+
+use super::hand::Hand;
+use super::rules::{Jokers, Ruleset};
+use super::HandTrait;
+
+// Scrambled but reproducible hands under R: the labels are the digits of a
+// Fibonacci hash of the hand's index
+fn generate_hands<R: Ruleset>(n: usize) -> Vec<String> {
+    let labels: Vec<_> = R::LABELS.chars().collect();
+    let base = labels.len() as u64;
+    (1..=n as u64)
+        .map(|i| {
+            let mut x = i.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+            (0..R::SIZE)
+                .map(|_| {
+                    let label = labels[(x % base) as usize];
+                    x /= base;
+                    label
+                })
+                .collect()
+        })
+        .collect()
+}
+
+pub fn run(n: usize) {
+    let hands = generate_hands::<Jokers>(n);
+    println!("{n} generated hands (with jokers)");
+
+    let start = Instant::now();
+    let parsed: Vec<_> = hands
+        .iter()
+        .map(|h| Hand::<Jokers>::parse(h).unwrap())
+        .collect();
+    let t_parse = start.elapsed();
+
+    let start = Instant::now();
+    let by_key: Vec<_> = parsed.iter().sorted().collect();
+    let t_key = start.elapsed();
+
+    let start = Instant::now();
+    let recomputed: Vec<_> = parsed
+        .iter()
+        .sorted_by(|a, b| a.cmp_recomputed(b))
+        .collect();
+    let t_recomputed = start.elapsed();
+    assert!(by_key.iter().zip(&recomputed).all(|(a, b)| a == b));
+
+    println!("{:>22}: {t_parse:>10.3?}", "parse + sort key");
+    for (name, t) in [("sort by key", t_key), ("sort, recomputing", t_recomputed)] {
+        println!(
+            "{name:>22}: {:>10.3?} ({:.1}x)",
+            t,
+            t_recomputed.as_secs_f64() / t.as_secs_f64()
+        );
+    }
+
+    let kinds = parsed.iter().map(Hand::kind).counts();
+    for (kind, count) in kinds.into_iter().sorted() {
+        println!("{:>22}: {count}", format!("{kind:?}"));
+    }
+}
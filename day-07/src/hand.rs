@@ -0,0 +1,313 @@
+use std::cmp::{Ordering, Reverse};
+use std::fmt;
+use std::marker::PhantomData;
+
+use super::rules::{Ruleset, TieBreak};
+use super::{HandKind, HandTrait};
+
+#[derive(Debug, Clone)]
+pub struct Hand<R> {
+    cards: Vec<usize>, // positions in R::LABELS, 0 is the strongest
+    kind: HandKind,
+    key: u64, // smaller is stronger, see sort_key()
+    rules: PhantomData<R>,
+}
+
+impl<R: Ruleset> Hand<R> {
+    // Four bits per label, and per signature and tie-break entry
+    const KEY_FITS: () = assert!(R::LABELS.len() <= 16 && R::SIZE <= 8);
+
+    fn is_wildcard(card: usize) -> bool {
+        R::WILDCARDS.contains(&R::LABELS[card..=card])
+    }
+
+    // Number of cards per label (wildcards left out), largest first
+    fn groups(cards: &[usize]) -> Vec<(usize, usize)> {
+        let mut counts = vec![0; R::LABELS.len()];
+        for &card in cards {
+            counts[card] += 1;
+        }
+        let mut groups: Vec<_> = counts
+            .into_iter()
+            .enumerate()
+            .filter(|&(card, count)| count > 0 && !Self::is_wildcard(card))
+            .map(|(card, count)| (count, card))
+            .collect();
+        groups.sort_by_key(|&(count, card)| (Reverse(count), card));
+        groups
+    }
+
+    // Group sizes once the wildcards have joined the largest group,
+    // e.g. [3, 1, 1] for a three of a kind. A larger signature is a better kind.
+    fn signature(cards: &[usize]) -> Vec<usize> {
+        let mut signature: Vec<_> = Self::groups(cards)
+            .into_iter()
+            .map(|(count, _)| count)
+            .collect();
+        let wildcards = cards.len() - signature.iter().sum::<usize>();
+        match signature.first_mut() {
+            Some(largest) => *largest += wildcards,
+            None => signature.push(wildcards),
+        }
+        signature
+    }
+
+    fn kind_of(signature: &[usize]) -> HandKind {
+        match (signature[0], signature.get(1).copied().unwrap_or(0)) {
+            (5.., _) => HandKind::FiveOfAKind,
+            (4, _) => HandKind::FourOfAKind,
+            (3, 2..) => HandKind::FullHouse,
+            (3, _) => HandKind::ThreeOfAKind,
+            (2, 2) => HandKind::TwoPair,
+            (2, _) => HandKind::OnePair,
+            (..) => HandKind::HighCard,
+        }
+    }
+
+    fn tie_break(cards: &[usize]) -> Vec<usize> {
+        match R::TIE_BREAK {
+            TieBreak::AsDealt => cards.to_vec(),
+            TieBreak::Strongest => {
+                let mut cards = cards.to_vec();
+                let groups = Self::groups(&cards);
+                let rank = |card| groups.iter().position(|&(_, c)| c == card);
+                // Wildcards don't belong to a group and come last
+                cards.sort_by_key(|&card| (rank(card).is_none(), rank(card), card));
+                cards
+            }
+        }
+    }
+
+    // The signature padded to SIZE entries (inverted, so that larger groups
+    // sort first), followed by the tie-break labels, four bits each. The kind
+    // is left out: comparing signatures already orders hands by kind first.
+    fn sort_key(signature: &[usize], tie_break: &[usize]) -> u64 {
+        let padded = (0..R::SIZE).map(|i| 15 - signature.get(i).copied().unwrap_or(0));
+        padded
+            .chain(tie_break.iter().copied())
+            .fold(0, |key, nibble| key << 4 | nibble as u64)
+    }
+
+    // The ordering the sort key encodes, computed from scratch
+    pub fn cmp_recomputed(&self, other: &Self) -> Ordering {
+        let kind = |cards| Self::kind_of(&Self::signature(cards));
+        kind(&self.cards)
+            .cmp(&kind(&other.cards))
+            .then_with(|| Self::signature(&other.cards).cmp(&Self::signature(&self.cards)))
+            .then_with(|| Self::tie_break(&self.cards).cmp(&Self::tie_break(&other.cards)))
+    }
+
+    fn labels(cards: &[usize]) -> String {
+        cards.iter().map(|&card| &R::LABELS[card..=card]).collect()
+    }
+
+    // The hand with every wildcard replaced by a label giving the same kind,
+    // or None if there are no wildcards
+    pub fn substituted(&self) -> Option<String> {
+        if !self.cards.iter().any(|&card| Self::is_wildcard(card)) {
+            return None;
+        }
+        // The strongest of the largest groups, or the strongest label if
+        // there are only wildcards
+        let target = match Self::groups(&self.cards).first() {
+            Some(&(_, card)) => card,
+            None => (0..R::LABELS.len()).find(|&card| !Self::is_wildcard(card))?,
+        };
+        let cards: Vec<_> = self
+            .cards
+            .iter()
+            .map(|&card| {
+                if Self::is_wildcard(card) {
+                    target
+                } else {
+                    card
+                }
+            })
+            .collect();
+        Some(Self::labels(&cards))
+    }
+}
+
+impl<R: Ruleset> fmt::Display for Hand<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&Self::labels(&self.cards))
+    }
+}
+
+impl<R: Ruleset> HandTrait for Hand<R> {
+    type Kind = HandKind;
+
+    fn parse(hand: &str) -> Result<Self, String> {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::KEY_FITS;
+        let cards = hand
+            .chars()
+            .map(|l| {
+                R::LABELS
+                    .find(l)
+                    .ok_or_else(|| format!("unknown label {l:?}"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if cards.len() != R::SIZE {
+            return Err(format!(
+                "expected {} cards, found {} in {hand:?}",
+                R::SIZE,
+                cards.len()
+            ));
+        }
+        let signature = Self::signature(&cards);
+        Ok(Self {
+            kind: Self::kind_of(&signature),
+            key: Self::sort_key(&signature, &Self::tie_break(&cards)),
+            cards,
+            rules: PhantomData,
+        })
+    }
+
+    fn kind(&self) -> HandKind {
+        self.kind
+    }
+}
+
+// Hands are equal when they rank the same, which under TieBreak::Strongest
+// includes reordered cards
+impl<R: Ruleset> PartialEq for Hand<R> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl<R: Ruleset> Eq for Hand<R> {}
+
+impl<R: Ruleset> PartialOrd for Hand<R> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Stronger hands come first
+impl<R: Ruleset> Ord for Hand<R> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::*;
+
+    #[derive(Debug, Clone)]
+    struct TwoJokers;
+
+    impl Ruleset for TwoJokers {
+        const LABELS: &'static str = "AKQT98765432JX";
+        const WILDCARDS: &'static str = "JX";
+        const SIZE: usize = 5;
+        const TIE_BREAK: TieBreak = TieBreak::AsDealt;
+    }
+
+    #[derive(Debug, Clone)]
+    struct SixCards;
+
+    impl Ruleset for SixCards {
+        const LABELS: &'static str = "AKQJT98765432";
+        const WILDCARDS: &'static str = "";
+        const SIZE: usize = 6;
+        const TIE_BREAK: TieBreak = TieBreak::Strongest;
+    }
+
+    fn kind<R: Ruleset>(hand: &str) -> HandKind {
+        Hand::<R>::parse(hand).unwrap().kind()
+    }
+
+    #[test]
+    fn test_parse() {
+        use crate::rules::Standard;
+
+        let error = |hand| Hand::<Standard>::parse(hand).unwrap_err();
+        assert_eq!(error("AKQJ"), "expected 5 cards, found 4 in \"AKQJ\"");
+        assert_eq!(error("AKQJT9"), "expected 5 cards, found 6 in \"AKQJT9\"");
+        assert_eq!(error("AKQJ1"), "unknown label '1'");
+        assert_eq!(error("akqjt"), "unknown label 'a'");
+        assert!(Hand::<SixCards>::parse("AKQJT9").is_ok());
+    }
+
+    #[test]
+    fn test_kind() {
+        use crate::rules::{Jokers, Standard};
+
+        assert_eq!(kind::<Standard>("KTJJT"), HandKind::TwoPair);
+        assert_eq!(kind::<Jokers>("KTJJT"), HandKind::FourOfAKind);
+        assert_eq!(kind::<Jokers>("JJJJJ"), HandKind::FiveOfAKind);
+        assert_eq!(kind::<Jokers>("2345J"), HandKind::OnePair);
+        assert_eq!(kind::<Jokers>("2233J"), HandKind::FullHouse);
+        assert_eq!(kind::<TwoJokers>("2X34J"), HandKind::ThreeOfAKind);
+        assert_eq!(kind::<TwoJokers>("22XJ3"), HandKind::FourOfAKind);
+        assert_eq!(kind::<SixCards>("222333"), HandKind::FullHouse);
+        assert_eq!(kind::<SixCards>("AA2233"), HandKind::TwoPair);
+    }
+
+    #[test]
+    fn test_substituted() {
+        use crate::rules::{Jokers, Standard};
+
+        let substituted = |hand| Hand::<Jokers>::parse(hand).unwrap().substituted();
+        assert_eq!(substituted("KTJJT").as_deref(), Some("KTTTT"));
+        assert_eq!(substituted("JJJJJ").as_deref(), Some("AAAAA"));
+        assert_eq!(substituted("2J3J4").as_deref(), Some("24344"));
+        assert_eq!(substituted("KK677"), None);
+        assert_eq!(
+            Hand::<Standard>::parse("KTJJT").unwrap().substituted(),
+            None
+        );
+
+        let hand = Hand::<TwoJokers>::parse("2X33J").unwrap();
+        assert_eq!(hand.to_string(), "2X33J");
+        assert_eq!(hand.substituted().as_deref(), Some("23333"));
+    }
+
+    #[test]
+    fn test_order() {
+        let hand = |hand| Hand::<SixCards>::parse(hand).unwrap();
+        // Four of a kind with a pair beats four of a kind with two kickers
+        assert!(hand("222233") < hand("2222AK"));
+        // Three pairs beat two pairs with an ace kicker
+        assert!(hand("223344") < hand("KKQQA2"));
+        // Kickers are compared after the groups, whatever the dealt order
+        assert!(hand("A2KK22") < hand("Q22KK2"));
+        assert!(hand("KK22A4") < hand("3KKA22"));
+        assert_eq!(hand("KK22A4"), hand("4KK2A2"));
+
+        let hand = |hand| Hand::<TwoJokers>::parse(hand).unwrap();
+        // Both jokers are weak on their own, X being the weakest
+        assert!(hand("2222J") < hand("2222X"));
+        assert!(hand("J2222") < hand("2222A"));
+    }
+
+    fn check_key_order<R: Ruleset>(labels: &str) {
+        let labels: Vec<_> = labels.chars().collect();
+        let hands: Vec<Hand<R>> = (0..R::SIZE)
+            .map(|_| labels.iter())
+            .multi_cartesian_product()
+            .step_by(7)
+            .map(|cards| Hand::parse(&cards.into_iter().collect::<String>()).unwrap())
+            .collect();
+        for pair in hands.windows(2) {
+            let [a, b] = pair else { unreachable!() };
+            assert_eq!(a.cmp(b), a.cmp_recomputed(b), "{:?} {:?}", a.cards, b.cards);
+        }
+    }
+
+    #[test]
+    fn test_key_matches_recomputed_order() {
+        use crate::rules::{Jokers, Kickers, Standard};
+
+        check_key_order::<Standard>("AKQJT92");
+        check_key_order::<Jokers>("AKQJT92");
+        check_key_order::<Kickers>("AKQJT92");
+        check_key_order::<TwoJokers>("AQJX32");
+        check_key_order::<SixCards>("AKJ32");
+    }
+}
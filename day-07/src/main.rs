@@ -1,6 +1,12 @@
+use std::fmt;
+
 use itertools::Itertools;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+use hand::Hand;
+use poker::PokerHand;
+use rules::{Jokers, Kickers, Standard};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 enum HandKind {
     FiveOfAKind,
     FourOfAKind,
@@ -11,31 +17,159 @@ enum HandKind {
     HighCard,
 }
 
-trait HandTrait: Ord {
-    fn new(hand: &str) -> Self;
-    fn kind(&self) -> HandKind;
+impl HandKind {
+    const ALL: [Self; 7] = [
+        Self::FiveOfAKind,
+        Self::FourOfAKind,
+        Self::FullHouse,
+        Self::ThreeOfAKind,
+        Self::TwoPair,
+        Self::OnePair,
+        Self::HighCard,
+    ];
+}
+
+trait HandTrait: Ord + Sized {
+    type Kind;
+
+    fn parse(hand: &str) -> Result<Self, String>;
+    fn kind(&self) -> Self::Kind;
 }
 
-mod part1;
-mod part2;
+mod bench;
+mod hand;
+mod poker;
+mod rules;
+mod standings;
+mod stats;
+
+#[derive(Debug, Clone, PartialEq)]
+enum GameError {
+    Syntax { line: usize, reason: String },
+    Overflow,
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax { line, reason } => write!(f, "line {line}: {reason}"),
+            Self::Overflow => write!(f, "total winnings do not fit into u64"),
+        }
+    }
+}
 
-fn evaluate<T: HandTrait>(input: &str) -> u32 {
+// One "HAND BID" per line; the bid is whatever follows the last space, so
+// hands may contain spaces themselves
+fn parse_game<T: HandTrait>(input: &str) -> Result<Vec<(T, u32)>, GameError> {
     input
         .lines()
-        .map(|s| {
-            let (hand, bid) = s.split_once(' ').unwrap();
-            (T::new(hand), bid.parse::<u32>().unwrap())
-        })
-        .sorted()
-        .rev()
         .enumerate()
-        .fold(0, |acc, (rank, (_, bid))| acc + (rank as u32 + 1) * bid)
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            let syntax = |reason| GameError::Syntax {
+                line: i + 1,
+                reason,
+            };
+            let (hand, bid) = line
+                .trim()
+                .rsplit_once(char::is_whitespace)
+                .ok_or_else(|| syntax("expected a hand and a bid".into()))?;
+            let bid = bid
+                .parse()
+                .map_err(|_| syntax(format!("invalid bid {bid:?}")))?;
+            Ok((T::parse(hand.trim_end()).map_err(syntax)?, bid))
+        })
+        .collect()
+}
+
+// Sum of rank * bid, with the bids ordered from rank 1 upwards
+fn total_winnings(bids: impl IntoIterator<Item = u64>) -> Result<u64, GameError> {
+    bids.into_iter()
+        .zip(1u64..)
+        .try_fold(0u64, |acc, (bid, rank)| {
+            rank.checked_mul(bid)
+                .and_then(|winnings| acc.checked_add(winnings))
+        })
+        .ok_or(GameError::Overflow)
+}
+
+fn evaluate<T: HandTrait>(input: &str) -> Result<u64, GameError> {
+    total_winnings(
+        parse_game::<T>(input)?
+            .into_iter()
+            .sorted()
+            .rev()
+            .map(|(_, bid)| bid as u64),
+    )
+}
+
+const USAGE: &str =
+    "usage: day-07 [--bench | --rules RULES | --poker | --stats | --standings RULES [--csv]]
+RULES: standard | jokers | kickers";
+
+fn run(text: &str, args: &[&str]) -> Result<(), GameError> {
+    match args {
+        [] => {
+            println!("Part 1: {}", evaluate::<Hand<Standard>>(text)?);
+            println!("Part 2: {}", evaluate::<Hand<Jokers>>(text)?);
+        }
+        ["--bench"] => bench::run(1_000_000),
+        ["--rules", "standard"] => println!("{}", evaluate::<Hand<Standard>>(text)?),
+        ["--rules", "jokers"] => println!("{}", evaluate::<Hand<Jokers>>(text)?),
+        ["--rules", "kickers"] => println!("{}", evaluate::<Hand<Kickers>>(text)?),
+        // A poker tournament on stdin, one "AH KD 7C 7S 2H BID" per line
+        ["--poker"] => {
+            let tournament = match std::io::read_to_string(std::io::stdin()) {
+                Ok(tournament) => tournament,
+                Err(err) => {
+                    eprintln!("Error: cannot read the tournament: {err}");
+                    std::process::exit(1);
+                }
+            };
+            println!("{}", evaluate::<PokerHand>(&tournament)?);
+        }
+        ["--stats"] => {
+            println!("Without jokers:");
+            let expected = stats::expected::<Standard>();
+            let observed = stats::observed::<Standard>(text)?;
+            println!("{}", stats::report(&expected, &observed));
+            println!("With jokers:");
+            let expected = stats::expected::<Jokers>();
+            let observed = stats::observed::<Jokers>(text)?;
+            print!("{}", stats::report(&expected, &observed));
+        }
+        ["--standings", rules, ref format @ ..] if matches!(format, [] | ["--csv"]) => {
+            let standings = match *rules {
+                "standard" => standings::standings::<Standard>(text)?,
+                "jokers" => standings::standings::<Jokers>(text)?,
+                "kickers" => standings::standings::<Kickers>(text)?,
+                _ => {
+                    eprintln!("{USAGE}");
+                    std::process::exit(1);
+                }
+            };
+            match format {
+                [] => print!("{}", standings::to_table(&standings)),
+                _ => print!("{}", standings::to_csv(&standings)),
+            }
+        }
+        _ => {
+            eprintln!("{USAGE}");
+            std::process::exit(1);
+        }
+    }
+    Ok(())
 }
 
 fn main() {
     let text = include_str!("../inputs/input.txt");
-    println!("Part 1: {}", evaluate::<part1::Hand>(text));
-    println!("Part 2: {}", evaluate::<part2::Hand>(text));
+
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    let args: Vec<_> = args.iter().map(String::as_str).collect();
+    if let Err(err) = run(text, &args) {
+        eprintln!("Error: {err}");
+        std::process::exit(1);
+    }
 }
 
 #[cfg(test)]
@@ -51,11 +185,54 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        assert_eq!(evaluate::<part1::Hand>(INPUT), 6440);
+        assert_eq!(evaluate::<Hand<Standard>>(INPUT), Ok(6440));
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(evaluate::<part2::Hand>(INPUT), 5905);
+        assert_eq!(evaluate::<Hand<Jokers>>(INPUT), Ok(5905));
+    }
+
+    #[test]
+    fn test_kickers() {
+        assert_eq!(evaluate::<Hand<Kickers>>(INPUT), Ok(6440));
+        // Aces over kings beat kings over queens, whatever is dealt first
+        let input = "2AAKK 1\nKKQQA 2";
+        assert_eq!(evaluate::<Hand<Standard>>(input), Ok(5));
+        assert_eq!(evaluate::<Hand<Kickers>>(input), Ok(4));
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let error = |input| evaluate::<Hand<Standard>>(input).unwrap_err().to_string();
+        assert_eq!(
+            error("32T3K 765\nT55J5"),
+            "line 2: expected a hand and a bid"
+        );
+        assert_eq!(error("32T3K 765\nT55J5 x"), "line 2: invalid bid \"x\"");
+        assert_eq!(error("32T3K -1"), "line 1: invalid bid \"-1\"");
+        assert_eq!(
+            error("32T3K 1\n\n32T3 2"),
+            "line 3: expected 5 cards, found 4 in \"32T3\""
+        );
+        assert_eq!(error("32T3Z 1"), "line 1: unknown label 'Z'");
+        assert_eq!(evaluate::<Hand<Standard>>("\n32T3K   7\n\n"), Ok(7));
+    }
+
+    #[test]
+    fn test_large_winnings() {
+        // Far beyond u32, comfortably within u64
+        let input = "AAAAA 4294967295\nKKKKK 4294967295";
+        assert_eq!(evaluate::<Hand<Standard>>(input), Ok(3 * 4294967295));
+    }
+
+    #[test]
+    fn test_overflow() {
+        assert_eq!(total_winnings([5, 7, 2]), Ok(5 + 14 + 6));
+        assert_eq!(total_winnings([u64::MAX]), Ok(u64::MAX));
+        // rank * bid
+        assert_eq!(total_winnings([0, u64::MAX]), Err(GameError::Overflow));
+        // The sum
+        assert_eq!(total_winnings([u64::MAX, 1]), Err(GameError::Overflow));
     }
 }
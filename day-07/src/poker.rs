@@ -0,0 +1,231 @@
+use std::cmp::{Ordering, Reverse};
+
+use super::HandTrait;
+
+// Strongest first, like HandKind
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PokerKind {
+    StraightFlush,
+    FourOfAKind,
+    FullHouse,
+    Flush,
+    Straight,
+    ThreeOfAKind,
+    TwoPair,
+    OnePair,
+    HighCard,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Card {
+    rank: u8, // 2..=14, aces high
+    suit: u8, // index into SUITS
+}
+
+const RANKS: &str = "23456789TJQKA";
+const SUITS: &str = "CDHS";
+
+impl Card {
+    // E.g. "AH" for the ace of hearts
+    fn parse(card: &str) -> Option<Self> {
+        let mut chars = card.chars();
+        let (rank, suit) = (chars.next()?, chars.next()?);
+        if chars.next().is_some() {
+            return None;
+        }
+        Some(Self {
+            rank: RANKS.find(rank)? as u8 + 2,
+            suit: SUITS.find(suit)? as u8,
+        })
+    }
+}
+
+// A five-card poker hand, e.g. "AH KD 7C 7S 2H"
+#[derive(Debug, Clone)]
+pub struct PokerHand {
+    kind: PokerKind,
+    key: u32, // kind, then the ranks deciding ties; smaller is stronger
+}
+
+impl PokerHand {
+    fn from_cards(cards: [Card; 5]) -> Self {
+        let mut counts = [0u8; 15];
+        for card in cards {
+            counts[card.rank as usize] += 1;
+        }
+        // Largest groups first, higher ranks first within the same group size
+        let mut ranks = cards.map(|card| card.rank);
+        ranks.sort_unstable_by_key(|&rank| Reverse((counts[rank as usize], rank)));
+
+        let first = counts[ranks[0] as usize];
+        let second = counts[ranks[first as usize] as usize];
+        let flush = cards.iter().all(|card| card.suit == cards[0].suit);
+        let distinct = first == 1;
+        // The wheel (A2345) is a straight with the ace counting as one
+        if distinct && ranks == [14, 5, 4, 3, 2] {
+            ranks = [5, 4, 3, 2, 1];
+        }
+        let straight = distinct && ranks[0] - ranks[4] == 4;
+
+        let kind = match (first, second) {
+            _ if straight && flush => PokerKind::StraightFlush,
+            (4, _) => PokerKind::FourOfAKind,
+            (3, 2) => PokerKind::FullHouse,
+            _ if flush => PokerKind::Flush,
+            _ if straight => PokerKind::Straight,
+            (3, _) => PokerKind::ThreeOfAKind,
+            (2, 2) => PokerKind::TwoPair,
+            (2, _) => PokerKind::OnePair,
+            _ => PokerKind::HighCard,
+        };
+        let key = ranks
+            .into_iter()
+            .fold(kind as u32, |key, rank| key << 4 | (15 - rank as u32));
+        Self { kind, key }
+    }
+}
+
+impl HandTrait for PokerHand {
+    type Kind = PokerKind;
+
+    fn parse(hand: &str) -> Result<Self, String> {
+        let cards = hand
+            .split_whitespace()
+            .map(|card| Card::parse(card).ok_or_else(|| format!("invalid card {card:?}")))
+            .collect::<Result<Vec<_>, _>>()?;
+        let cards: [Card; 5] = cards
+            .try_into()
+            .map_err(|cards: Vec<_>| format!("expected 5 cards, found {}", cards.len()))?;
+        for (i, card) in cards.iter().enumerate() {
+            if cards[..i].contains(card) {
+                return Err(format!("{hand:?} has the same card twice"));
+            }
+        }
+        Ok(Self::from_cards(cards))
+    }
+
+    fn kind(&self) -> PokerKind {
+        self.kind
+    }
+}
+
+impl PartialEq for PokerHand {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl Eq for PokerHand {}
+
+impl PartialOrd for PokerHand {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Stronger hands come first
+impl Ord for PokerHand {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use itertools::Itertools;
+
+    use super::*;
+    use crate::evaluate;
+
+    fn hand(hand: &str) -> PokerHand {
+        PokerHand::parse(hand).unwrap()
+    }
+
+    #[test]
+    fn test_kind() {
+        for (cards, kind) in [
+            ("TH JH QH KH AH", PokerKind::StraightFlush),
+            ("5D 4D 3D 2D AD", PokerKind::StraightFlush),
+            ("9C 9D 9H 9S 2C", PokerKind::FourOfAKind),
+            ("3C 3D 3H 2S 2C", PokerKind::FullHouse),
+            ("2S 7S 9S JS KS", PokerKind::Flush),
+            ("AC 2D 3H 4S 5C", PokerKind::Straight),
+            ("TC JD QH KS AC", PokerKind::Straight),
+            ("QC KD AH 2S 3C", PokerKind::HighCard),
+            ("7C 7D 7H KS 2C", PokerKind::ThreeOfAKind),
+            ("7C 7D KH KS 2C", PokerKind::TwoPair),
+            ("7C 7D 3H KS 2C", PokerKind::OnePair),
+            ("7C 8D 3H KS 2C", PokerKind::HighCard),
+        ] {
+            assert_eq!(hand(cards).kind(), kind, "{cards}");
+        }
+    }
+
+    #[test]
+    fn test_order() {
+        // The wheel is the lowest straight
+        assert!(hand("2C 3D 4H 5S 6C") < hand("AC 2D 3H 4S 5C"));
+        // Pairs first, then kickers
+        assert!(hand("KC KD 2H 3S 4C") < hand("QC QD AH KS JC"));
+        assert!(hand("KC KD 9H 3S 2C") < hand("KH KS 8H 7S 6C"));
+        assert!(hand("8C 8D 4H 4S AC") < hand("8H 8S 4C 4D KC"));
+        // Full houses are decided by the triple
+        assert!(hand("3C 3D 3H 2S 2C") < hand("2H 2S 2D AS AC"));
+        // Suits don't matter
+        assert_eq!(hand("AC KD QH JS 9C"), hand("AD KH QS JC 9D"));
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let input = "\
+            AH KH QH JH TH 10\n\
+            2C 2D 3H 3S 4C 20\n\
+            AC AD KH KS 2C 30";
+        assert_eq!(evaluate::<PokerHand>(input), Ok(3 * 10 + 2 * 30 + 20));
+    }
+
+    #[test]
+    fn test_parse() {
+        let error = |hand| PokerHand::parse(hand).unwrap_err();
+        assert_eq!(
+            error("AH AH KD QC JS"),
+            "\"AH AH KD QC JS\" has the same card twice"
+        );
+        assert_eq!(error("AH KD QC JS"), "expected 5 cards, found 4");
+        assert_eq!(error("AH KD QC JS 1S"), "invalid card \"1S\"");
+        assert_eq!(error("AH KD QC JS TSS"), "invalid card \"TSS\"");
+        assert_eq!(error("AH KD QC JS TX"), "invalid card \"TX\"");
+    }
+
+    #[test]
+    fn test_all_hands() {
+        let deck: Vec<_> = (0..52)
+            .map(|i| Card {
+                rank: i / 4 + 2,
+                suit: i % 4,
+            })
+            .collect();
+        let mut kinds = HashMap::new();
+        let mut keys = HashSet::new();
+        for cards in deck.into_iter().combinations(5) {
+            let hand = PokerHand::from_cards(cards.try_into().unwrap());
+            *kinds.entry(hand.kind).or_insert(0) += 1;
+            keys.insert(hand.key);
+        }
+
+        assert_eq!(kinds.values().sum::<u32>(), 2_598_960);
+        assert_eq!(kinds[&PokerKind::StraightFlush], 40);
+        assert_eq!(kinds[&PokerKind::FourOfAKind], 624);
+        assert_eq!(kinds[&PokerKind::FullHouse], 3_744);
+        assert_eq!(kinds[&PokerKind::Flush], 5_108);
+        assert_eq!(kinds[&PokerKind::Straight], 10_200);
+        assert_eq!(kinds[&PokerKind::ThreeOfAKind], 54_912);
+        assert_eq!(kinds[&PokerKind::TwoPair], 123_552);
+        assert_eq!(kinds[&PokerKind::OnePair], 1_098_240);
+        assert_eq!(kinds[&PokerKind::HighCard], 1_302_540);
+        // Distinct hand values, once suits no longer matter
+        assert_eq!(keys.len(), 7_462);
+    }
+}
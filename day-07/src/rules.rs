@@ -0,0 +1,46 @@
+// How the cards of two hands of the same kind are compared
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TieBreak {
+    AsDealt,   // card by card in the order they were dealt
+    Strongest, // largest groups first, then by label (like poker kickers)
+}
+
+// A Camel Cards variant
+pub trait Ruleset {
+    const LABELS: &'static str; // strongest first
+    const WILDCARDS: &'static str; // labels standing in for whatever makes the best kind
+    const SIZE: usize;
+    const TIE_BREAK: TieBreak;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Standard;
+
+impl Ruleset for Standard {
+    const LABELS: &'static str = "AKQJT98765432";
+    const WILDCARDS: &'static str = "";
+    const SIZE: usize = 5;
+    const TIE_BREAK: TieBreak = TieBreak::AsDealt;
+}
+
+// J is a joker, and the weakest card on its own
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Jokers;
+
+impl Ruleset for Jokers {
+    const LABELS: &'static str = "AKQT98765432J";
+    const WILDCARDS: &'static str = "J";
+    const SIZE: usize = 5;
+    const TIE_BREAK: TieBreak = TieBreak::AsDealt;
+}
+
+// No wildcards, and hands of the same kind compare like in poker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Kickers;
+
+impl Ruleset for Kickers {
+    const LABELS: &'static str = "AKQJT98765432";
+    const WILDCARDS: &'static str = "";
+    const SIZE: usize = 5;
+    const TIE_BREAK: TieBreak = TieBreak::Strongest;
+}
@@ -0,0 +1,131 @@
+use itertools::Itertools;
+
+use super::hand::Hand;
+use super::rules::Ruleset;
+use super::{parse_game, GameError, HandKind, HandTrait};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Standing {
+    pub rank: u64, // 1 is the weakest hand
+    pub hand: String,
+    pub kind: HandKind,
+    pub bid: u32,
+    pub winnings: u64,
+    pub substituted: Option<String>, // the hand with its wildcards played
+}
+
+impl Standing {
+    // "KTJJT → KTTTT" when wildcards were played, the plain hand otherwise
+    fn hand_str(&self) -> String {
+        match &self.substituted {
+            Some(substituted) => format!("{} → {substituted}", self.hand),
+            None => self.hand.clone(),
+        }
+    }
+}
+
+// From the weakest hand to the strongest
+pub fn standings<R: Ruleset>(input: &str) -> Result<Vec<Standing>, GameError> {
+    parse_game::<Hand<R>>(input)?
+        .into_iter()
+        .sorted()
+        .rev()
+        .zip(1u64..)
+        .map(|((hand, bid), rank)| {
+            Ok(Standing {
+                rank,
+                hand: hand.to_string(),
+                kind: hand.kind(),
+                bid,
+                winnings: rank.checked_mul(bid as u64).ok_or(GameError::Overflow)?,
+                substituted: hand.substituted(),
+            })
+        })
+        .collect()
+}
+
+// Numbers right aligned, the kind and hand left aligned
+pub fn to_table(standings: &[Standing]) -> String {
+    let mut rows = vec![["rank", "kind", "bid", "winnings", "hand"].map(String::from)];
+    rows.extend(standings.iter().map(|s| {
+        [
+            s.rank.to_string(),
+            format!("{:?}", s.kind),
+            s.bid.to_string(),
+            s.winnings.to_string(),
+            s.hand_str(),
+        ]
+    }));
+
+    let mut widths = [0; 5];
+    for row in &rows {
+        for (w, cell) in widths.iter_mut().zip(row) {
+            *w = (*w).max(cell.chars().count());
+        }
+    }
+    rows.insert(1, widths.map(|w| "-".repeat(w)));
+
+    let [rank_w, kind_w, bid_w, winnings_w, _] = widths;
+    rows.iter()
+        .map(|[rank, kind, bid, winnings, hand]| {
+            let line = format!(
+                "{rank:>rank_w$}  {kind:<kind_w$}  {bid:>bid_w$}  {winnings:>winnings_w$}  {hand}"
+            );
+            format!("{}\n", line.trim_end())
+        })
+        .collect()
+}
+
+pub fn to_csv(standings: &[Standing]) -> String {
+    let rows = standings.iter().map(|s| {
+        let substituted = s.substituted.as_deref().unwrap_or("");
+        format!(
+            "{},{},{:?},{},{},{substituted}\n",
+            s.rank, s.hand, s.kind, s.bid, s.winnings
+        )
+    });
+    std::iter::once("rank,hand,kind,bid,winnings,substituted\n".to_string())
+        .chain(rows)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::{Jokers, Standard};
+
+    const INPUT: &str = "\
+        32T3K 765\n\
+        T55J5 684\n\
+        KK677 28\n\
+        KTJJT 220\n\
+        QQQJA 483";
+
+    #[test]
+    fn test_standings() {
+        let jokers = standings::<Jokers>(INPUT).unwrap();
+        assert_eq!(jokers.iter().map(|s| s.winnings).sum::<u64>(), 5905);
+        assert_eq!(
+            to_csv(&jokers),
+            "\
+            rank,hand,kind,bid,winnings,substituted\n\
+            1,32T3K,OnePair,765,765,\n\
+            2,KK677,TwoPair,28,56,\n\
+            3,T55J5,FourOfAKind,684,2052,T5555\n\
+            4,QQQJA,FourOfAKind,483,1932,QQQQA\n\
+            5,KTJJT,FourOfAKind,220,1100,KTTTT\n"
+        );
+        assert_eq!(
+            to_table(&jokers[2..4]),
+            "\
+            rank  kind         bid  winnings  hand\n\
+            ----  -----------  ---  --------  -------------\n   \
+            3  FourOfAKind  684      2052  T55J5 → T5555\n   \
+            4  FourOfAKind  483      1932  QQQJA → QQQQA\n"
+        );
+
+        let standard = standings::<Standard>(INPUT).unwrap();
+        assert_eq!(standard.iter().map(|s| s.winnings).sum::<u64>(), 6440);
+        assert!(standard.iter().all(|s| s.substituted.is_none()));
+    }
+}
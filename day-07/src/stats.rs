@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use itertools::Itertools;
+
+use super::hand::Hand;
+use super::rules::Ruleset;
+use super::{parse_game, GameError, HandKind, HandTrait};
+
+// Number of hands of each kind
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Distribution(HashMap<HandKind, u64>);
+
+impl Distribution {
+    pub fn count(&self, kind: HandKind) -> u64 {
+        self.0.get(&kind).copied().unwrap_or(0)
+    }
+
+    pub fn total(&self) -> u64 {
+        self.0.values().sum()
+    }
+
+    pub fn probability(&self, kind: HandKind) -> f64 {
+        self.count(kind) as f64 / self.total() as f64
+    }
+}
+
+impl FromIterator<HandKind> for Distribution {
+    fn from_iter<I: IntoIterator<Item = HandKind>>(iter: I) -> Self {
+        Self(
+            iter.into_iter()
+                .counts()
+                .into_iter()
+                .map(|(k, n)| (k, n as u64))
+                .collect(),
+        )
+    }
+}
+
+// Kinds of all LABELS.len()^SIZE label sequences
+pub fn expected<R: Ruleset>() -> Distribution {
+    (0..R::SIZE)
+        .map(|_| R::LABELS.chars())
+        .multi_cartesian_product()
+        .map(|labels| {
+            let hand = Hand::<R>::parse(&labels.into_iter().collect::<String>());
+            hand.expect("every label sequence is a valid hand").kind()
+        })
+        .collect()
+}
+
+// Kinds of the hands in a puzzle input
+pub fn observed<R: Ruleset>(input: &str) -> Result<Distribution, GameError> {
+    Ok(parse_game::<Hand<R>>(input)?
+        .iter()
+        .map(|(hand, _)| hand.kind())
+        .collect())
+}
+
+// Pearson's chi-squared statistic of the observed counts against the expected
+// probabilities; kinds that can't occur are left out. No hands fit any
+// distribution perfectly.
+pub fn chi_squared(expected: &Distribution, observed: &Distribution) -> f64 {
+    if observed.total() == 0 {
+        return 0.0;
+    }
+    HandKind::ALL
+        .into_iter()
+        .filter(|&kind| expected.count(kind) > 0)
+        .map(|kind| {
+            let e = expected.probability(kind) * observed.total() as f64;
+            (observed.count(kind) as f64 - e).powi(2) / e
+        })
+        .sum()
+}
+
+pub fn report(expected: &Distribution, observed: &Distribution) -> String {
+    let mut output = format!(
+        "{:<14} {:>8} {:>11} {:>9} {:>9}\n",
+        "kind", "count", "probability", "observed", "expected"
+    );
+    for kind in HandKind::ALL {
+        writeln!(
+            output,
+            "{:<14} {:>8} {:>11.6} {:>9} {:>9.1}",
+            format!("{kind:?}"),
+            expected.count(kind),
+            expected.probability(kind),
+            observed.count(kind),
+            expected.probability(kind) * observed.total() as f64
+        )
+        .unwrap();
+    }
+    writeln!(
+        output,
+        "{:<14} {:>8} {:>11} {:>9}",
+        "total",
+        expected.total(),
+        "",
+        observed.total()
+    )
+    .unwrap();
+    writeln!(
+        output,
+        "chi-squared: {:.2}",
+        chi_squared(expected, observed)
+    )
+    .unwrap();
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::{Jokers, Standard};
+
+    #[test]
+    fn test_standard() {
+        let distribution = expected::<Standard>();
+        assert_eq!(distribution.total(), 13u64.pow(5));
+        for (kind, count) in [
+            (HandKind::FiveOfAKind, 13),
+            (HandKind::FourOfAKind, 13 * 12 * 5),
+            (HandKind::FullHouse, 13 * 12 * 10),
+            (HandKind::ThreeOfAKind, 13 * 66 * 20),
+            (HandKind::TwoPair, 78 * 11 * 30),
+            (HandKind::OnePair, 13 * 220 * 60),
+            (HandKind::HighCard, 13 * 12 * 11 * 10 * 9),
+        ] {
+            assert_eq!(distribution.count(kind), count, "{kind:?}");
+        }
+    }
+
+    // The kind a joker-free hand has without any wildcards
+    fn plain_kind(cards: &[u8]) -> HandKind {
+        let mut counts = [0u8; 13];
+        for &card in cards {
+            counts[card as usize] += 1;
+        }
+        counts.sort_unstable_by(|a, b| b.cmp(a));
+        match (counts[0], counts[1]) {
+            (5, _) => HandKind::FiveOfAKind,
+            (4, _) => HandKind::FourOfAKind,
+            (3, 2) => HandKind::FullHouse,
+            (3, _) => HandKind::ThreeOfAKind,
+            (2, 2) => HandKind::TwoPair,
+            (2, _) => HandKind::OnePair,
+            _ => HandKind::HighCard,
+        }
+    }
+
+    // Every joker independently tries every other label; the order in which
+    // they are placed doesn't matter for the kind
+    fn best_substitution(cards: &[u8], joker: u8) -> HandKind {
+        let (jokers, mut plain): (Vec<_>, Vec<_>) = cards.iter().partition(|&&c| c == joker);
+        let others = (0..13).filter(|&label| label != joker);
+        others
+            .combinations_with_replacement(jokers.len())
+            .map(|labels| {
+                plain.truncate(cards.len() - jokers.len());
+                plain.extend(labels);
+                plain_kind(&plain)
+            })
+            .min()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_jokers_match_brute_force() {
+        let joker = Jokers::LABELS.find('J').unwrap() as u8;
+        let labels: Vec<_> = Jokers::LABELS.chars().collect();
+        // The kind only depends on which cards there are, not on their order
+        let mut best = HashMap::new();
+        for cards in (0..5).map(|_| 0..13u8).multi_cartesian_product() {
+            let hand: String = cards.iter().map(|&c| labels[c as usize]).collect();
+            let sorted: Vec<_> = cards.iter().copied().sorted().collect();
+            let expected = *best
+                .entry(sorted)
+                .or_insert_with(|| best_substitution(&cards, joker));
+            assert_eq!(
+                Hand::<Jokers>::parse(&hand).unwrap().kind(),
+                expected,
+                "{hand}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_observed() {
+        let input = "32T3K 765\nT55J5 684\nKK677 28\nKTJJT 220\nQQQJA 483";
+        let standard = observed::<Standard>(input).unwrap();
+        assert_eq!(standard.count(HandKind::ThreeOfAKind), 2);
+        assert_eq!(standard.count(HandKind::TwoPair), 2);
+        let jokers = observed::<Jokers>(input).unwrap();
+        assert_eq!(jokers.count(HandKind::FourOfAKind), 3);
+        assert_eq!(jokers.total(), 5);
+
+        let uniform: Distribution = HandKind::ALL.into_iter().collect();
+        let observed: Distribution = HandKind::ALL.into_iter().collect();
+        assert_eq!(chi_squared(&uniform, &observed), 0.0);
+    }
+
+    #[test]
+    fn test_no_hands() {
+        let observed = observed::<Standard>("").unwrap();
+        assert_eq!(observed.total(), 0);
+        assert_eq!(chi_squared(&expected::<Standard>(), &observed), 0.0);
+        assert!(report(&expected::<Standard>(), &observed).ends_with("chi-squared: 0.00\n"));
+    }
+}
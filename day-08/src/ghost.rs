@@ -0,0 +1,161 @@
+// A ghost's walk over (node, instruction index) states ends up in a cycle,
+// so the steps at which it stands on an end node are a finite set of early
+// hits plus a few residues modulo the cycle length
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ghost {
+    pub entry: u64,           // step at which the cycle is entered
+    pub period: u64,          // length of the cycle
+    pub early_hits: Vec<u64>, // end nodes reached before the cycle
+    pub cycle_hits: Vec<u64>, // end nodes within [entry, entry + period)
+}
+
+impl Ghost {
+    // `graph` and `start` are packed as in parse(): a node at index i has its
+    // left and right successors at i and i + 1
+    pub fn trace(instructions: &[usize], graph: &[usize], start: usize, is_end: &[bool]) -> Self {
+        let len = instructions.len();
+        let mut seen = vec![u64::MAX; graph.len() / 2 * len];
+        let mut hits = Vec::new();
+        let (mut node, mut step) = (start, 0u64);
+        loop {
+            let phase = step as usize % len;
+            let state = node / 2 * len + phase;
+            if seen[state] != u64::MAX {
+                let entry = seen[state];
+                let split = hits.partition_point(|&hit| hit < entry);
+                let cycle_hits = hits.split_off(split);
+                return Self {
+                    entry,
+                    period: step - entry,
+                    early_hits: hits,
+                    cycle_hits,
+                };
+            }
+            seen[state] = step;
+            if is_end[node] {
+                hits.push(step);
+            }
+            node = graph[node + instructions[phase]];
+            step += 1;
+        }
+    }
+
+    pub fn is_at_end(&self, step: u64) -> bool {
+        if step < self.entry {
+            self.early_hits.contains(&step)
+        } else {
+            let offset = (step - self.entry) % self.period;
+            self.cycle_hits.contains(&(self.entry + offset))
+        }
+    }
+}
+
+fn gcd(a: u128, b: u128) -> u128 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+// Inverse of a modulo m, for coprime a and m
+fn mod_inverse(a: u128, m: u128) -> u128 {
+    let (mut old_r, mut r) = (a as i128 % m as i128, m as i128);
+    let (mut old_s, mut s) = (1i128, 0i128);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_s, s) = (s, old_s - q * s);
+    }
+    old_s.rem_euclid(m as i128) as u128
+}
+
+// Generalised CRT: x = a (mod m) and x = b (mod n) for moduli that need not
+// be coprime. Returns (x, lcm(m, n)) or None if the congruences contradict.
+pub fn crt((a, m): (u128, u128), (b, n): (u128, u128)) -> Option<(u128, u128)> {
+    let g = gcd(m, n);
+    let diff = (b % n + n - a % n) % n; // (b - a) mod n, kept non-negative
+    if diff % g != 0 {
+        return None;
+    }
+    let (m_g, n_g) = (m / g, n / g);
+    let lcm = m.checked_mul(n_g).expect("combined period overflows u128");
+    // a + m * k = b (mod n) <=> (m / g) * k = (b - a) / g (mod n / g)
+    let k = (diff / g) % n_g * mod_inverse(m_g % n_g, n_g) % n_g;
+    Some(((a + m * k) % lcm, lcm))
+}
+
+// First step at which every ghost stands on an end node at once
+pub fn first_common_hit(ghosts: &[Ghost]) -> Option<u64> {
+    // Before everybody is in their cycle, simply check every step
+    let settled = ghosts.iter().map(|g| g.entry).max()?;
+    if let Some(step) = (0..settled).find(|&step| ghosts.iter().all(|g| g.is_at_end(step))) {
+        return Some(step);
+    }
+
+    // From then on, every ghost restricts the step to one of its residues
+    let mut solutions = vec![(0u128, 1u128)];
+    for ghost in ghosts {
+        let period = ghost.period as u128;
+        solutions = solutions
+            .iter()
+            .flat_map(|&solution| {
+                ghost
+                    .cycle_hits
+                    .iter()
+                    .filter_map(move |&hit| crt(solution, (hit as u128 % period, period)))
+            })
+            .collect();
+    }
+
+    // Smallest representative of each residue class from `settled` on
+    let settled = settled as u128;
+    solutions
+        .into_iter()
+        .map(|(x, m)| x + (settled.saturating_sub(x)).div_ceil(m) * m)
+        .min()
+        .map(|step| u64::try_from(step).expect("step count overflows u64"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt((2, 3), (3, 5)), Some((8, 15)));
+        assert_eq!(crt((1, 4), (3, 6)), Some((9, 12)));
+        assert_eq!(crt((1, 4), (2, 6)), None);
+        assert_eq!(crt((0, 1), (4, 7)), Some((4, 7)));
+        assert_eq!(crt((5, 10), (5, 10)), Some((5, 10)));
+        // m * n would overflow u128
+        assert_eq!(crt((3, 1 << 100), (3, 1 << 100)), Some((3, 1 << 100)));
+    }
+
+    fn ghost(entry: u64, period: u64, early_hits: &[u64], cycle_hits: &[u64]) -> Ghost {
+        Ghost {
+            entry,
+            period,
+            early_hits: early_hits.to_vec(),
+            cycle_hits: cycle_hits.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_first_common_hit() {
+        // Steps 1, 5, 9, ... and 3, 6, 9, ...
+        let ghosts = [ghost(1, 4, &[], &[1]), ghost(1, 3, &[], &[3])];
+        assert_eq!(first_common_hit(&ghosts), Some(9));
+        // Two ends in a cycle: steps 2, 4, 7, 9, ... and 4, 9, ...
+        let ghosts = [ghost(0, 5, &[], &[2, 4]), ghost(0, 5, &[], &[4])];
+        assert_eq!(first_common_hit(&ghosts), Some(4));
+        let ghosts = [ghost(0, 5, &[], &[2, 4]), ghost(0, 5, &[], &[3])];
+        assert_eq!(first_common_hit(&ghosts), None);
+        // Only an early hit at step 2
+        let ghosts = [ghost(3, 2, &[2], &[]), ghost(1, 1, &[], &[1])];
+        assert_eq!(first_common_hit(&ghosts), Some(2));
+        // Early hits alone don't meet the later residues
+        let ghosts = [ghost(3, 2, &[2], &[4]), ghost(0, 3, &[], &[0])];
+        assert_eq!(first_common_hit(&ghosts), Some(6));
+    }
+}
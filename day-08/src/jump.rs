@@ -0,0 +1,194 @@
+// Binary lifting over (node, instruction index) states: level k maps every
+// state to the state 2^k steps later and records whether an end node is
+// visited on the way (the state itself included, the final one not)
+pub struct JumpTable {
+    phases: usize,          // number of instructions
+    next: Vec<Vec<u32>>,    // next[k][state]
+    has_end: Vec<Vec<u64>>, // has_end[k], one bit per state
+}
+
+impl JumpTable {
+    // Enough levels to answer queries of up to `max_steps` steps
+    pub fn new(instructions: &[usize], graph: &[usize], is_end: &[bool], max_steps: u64) -> Self {
+        let phases = instructions.len();
+        let states = graph.len() / 2 * phases;
+        assert!(
+            states < u32::MAX as usize,
+            "too many states for a jump table"
+        );
+
+        let mut next = vec![(0..states)
+            .map(|state| {
+                let (node, phase) = (state / phases * 2, state % phases);
+                let successor = graph[node + instructions[phase]];
+                (successor / 2 * phases + (phase + 1) % phases) as u32
+            })
+            .collect::<Vec<_>>()];
+        let mut has_end = vec![vec![0u64; states.div_ceil(64)]];
+        for state in (0..states).filter(|state| is_end[state / phases * 2]) {
+            has_end[0][state / 64] |= 1 << (state % 64);
+        }
+
+        let levels = (u64::BITS - max_steps.leading_zeros()).max(1) as usize;
+        for k in 1..levels {
+            let (prev, prev_end) = (&next[k - 1], &has_end[k - 1]);
+            let bit = |state: usize| prev_end[state / 64] >> (state % 64) & 1;
+            let mut level_end = vec![0u64; states.div_ceil(64)];
+            let level: Vec<_> = (0..states)
+                .map(|state| {
+                    let half = prev[state] as usize;
+                    level_end[state / 64] |= (bit(state) | bit(half)) << (state % 64);
+                    prev[half]
+                })
+                .collect();
+            next.push(level);
+            has_end.push(level_end);
+        }
+        Self {
+            phases,
+            next,
+            has_end,
+        }
+    }
+
+    // A packed node (as in parse()) at the first instruction
+    pub fn state(&self, node: usize) -> usize {
+        node / 2 * self.phases
+    }
+
+    pub fn node(&self, state: usize) -> usize {
+        state / self.phases * 2
+    }
+
+    fn has_end(&self, k: usize, state: usize) -> bool {
+        self.has_end[k][state / 64] >> (state % 64) & 1 == 1
+    }
+
+    fn check_range(&self, steps: u64) {
+        assert!(
+            steps >> self.next.len() == 0,
+            "{steps} steps is beyond the table's {} levels",
+            self.next.len()
+        );
+    }
+
+    // State after `steps` steps
+    pub fn after(&self, mut state: usize, steps: u64) -> usize {
+        self.check_range(steps);
+        for (k, level) in self.next.iter().enumerate() {
+            if steps >> k & 1 == 1 {
+                state = level[state] as usize;
+            }
+        }
+        state
+    }
+
+    // First step in 0..=steps at which the walk stands on an end node
+    pub fn first_end(&self, mut state: usize, steps: u64) -> Option<u64> {
+        self.check_range(steps);
+        // Skip the longest prefix without end nodes, largest blocks first
+        let mut skipped = 0;
+        for k in (0..self.next.len()).rev() {
+            if skipped + (1 << k) <= steps && !self.has_end(k, state) {
+                state = self.next[k][state] as usize;
+                skipped += 1 << k;
+            }
+        }
+        // The walk is now at most one step short of the end of the range
+        if self.has_end(0, state) {
+            Some(skipped)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ghost::Ghost;
+    use crate::parse;
+
+    const INPUT: &str = "\
+        LR\n\
+        \n\
+        11A = (11B, XXX)\n\
+        11B = (XXX, 11Z)\n\
+        11Z = (11B, XXX)\n\
+        22A = (22B, XXX)\n\
+        22B = (22C, 22C)\n\
+        22C = (22Z, 22Z)\n\
+        22Z = (22B, 22B)\n\
+        XXX = (XXX, XXX)";
+
+    #[test]
+    fn test_matches_simulation() {
+        let network = parse(INPUT);
+        let is_end: Vec<_> = (0..network.graph.len())
+            .map(|node| {
+                network
+                    .nodes
+                    .iter()
+                    .any(|(n, &i)| i == node && n.ends_with('Z'))
+            })
+            .collect();
+        let table = JumpTable::new(&network.instructions, &network.graph, &is_end, 100);
+
+        for &start in network.nodes.values() {
+            let mut node = start;
+            let mut first_end = None;
+            for step in 0..100u64 {
+                let state = table.state(start);
+                assert_eq!(table.node(table.after(state, step)), node);
+                if is_end[node] {
+                    first_end = first_end.or(Some(step));
+                }
+                assert_eq!(table.first_end(state, step), first_end, "{start} {step}");
+                node = network.graph[node + network.instructions[step as usize % 2]];
+            }
+        }
+    }
+
+    #[test]
+    fn test_far_future() {
+        let network = parse(INPUT);
+        let node = |name| network.nodes[name];
+        let is_end: Vec<_> = (0..network.graph.len())
+            .map(|idx| idx == node("11Z") || idx == node("22Z"))
+            .collect();
+        let steps = 10u64.pow(18);
+        let table = JumpTable::new(&network.instructions, &network.graph, &is_end, steps);
+
+        // 22A reaches 22B after one step, then cycles through 22B, 22C, 22Z
+        let start = table.state(node("22A"));
+        let expected = ["22B", "22C", "22Z"][((steps - 1) % 3) as usize];
+        assert_eq!(table.node(table.after(start, steps)), node(expected));
+        // 11A alternates between 11B and 11Z from step 1 on
+        let start = table.state(node("11A"));
+        assert_eq!(table.node(table.after(start, steps)), node("11Z"));
+        assert_eq!(table.node(table.after(start, steps - 1)), node("11B"));
+
+        // First ends agree with the cycle analysis
+        for name in ["11A", "22A", "XXX"] {
+            let ghost = Ghost::trace(&network.instructions, &network.graph, node(name), &is_end);
+            let first = ghost
+                .early_hits
+                .iter()
+                .chain(&ghost.cycle_hits)
+                .next()
+                .copied();
+            assert_eq!(table.first_end(table.state(node(name)), steps), first);
+        }
+        assert_eq!(table.first_end(table.state(node("22A")), 2), None);
+        assert_eq!(table.first_end(table.state(node("22A")), 3), Some(3));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_out_of_range() {
+        let network = parse(INPUT);
+        let is_end = vec![false; network.graph.len()];
+        let table = JumpTable::new(&network.instructions, &network.graph, &is_end, 1000);
+        table.after(0, 1 << 10);
+    }
+}
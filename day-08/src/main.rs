@@ -1,6 +1,38 @@
 use std::collections::HashMap;
+use std::fmt;
 
-fn parse(input: &str, use_wildcards: bool) -> (Vec<usize>, Vec<usize>, Vec<usize>, Vec<usize>) {
+use ghost::{first_common_hit, Ghost};
+use jump::JumpTable;
+use pattern::Pattern;
+
+mod ghost;
+mod jump;
+mod pattern;
+
+#[derive(Debug, Clone, PartialEq)]
+enum NetworkError {
+    NoMatch { role: &'static str, pattern: String },
+    UnknownNode(String),
+}
+
+impl fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoMatch { role, pattern } => {
+                write!(f, "{role} pattern {pattern:?} matches no node")
+            }
+            Self::UnknownNode(name) => write!(f, "there is no node {name:?}"),
+        }
+    }
+}
+
+struct Network<'a> {
+    instructions: Vec<usize>,
+    graph: Vec<usize>, // left and right successor of node i at 2 * i and 2 * i + 1
+    nodes: HashMap<&'a str, usize>, // name -> index into graph
+}
+
+fn parse(input: &str) -> Network<'_> {
     let mut lines = input.lines();
 
     let instructions = lines
@@ -21,89 +53,179 @@ fn parse(input: &str, use_wildcards: bool) -> (Vec<usize>, Vec<usize>, Vec<usize
         .collect();
 
     let mut packed_graph = vec![usize::MAX; 2 * graph.len()];
-    let lut: HashMap<_, _> = graph.keys().zip(0..).map(|(k, v)| (k, 2 * v)).collect();
+    let lut: HashMap<_, _> = graph.keys().zip(0..).map(|(&k, v)| (k, 2 * v)).collect();
     for (node, (left, right)) in &graph {
         packed_graph[lut[node]] = lut[left];
         packed_graph[lut[node] + 1] = lut[right];
     }
 
-    let endpoints = |name: &str| {
-        if use_wildcards {
-            let pattern = name.chars().last().unwrap();
-            lut.iter()
-                .filter_map(|(label, idx)| {
-                    if label.ends_with(pattern) {
-                        Some(*idx)
-                    } else {
-                        None
-                    }
-                })
-                .collect()
-        } else {
-            vec![lut[&name]]
-        }
-    };
-
-    let starts = endpoints("AAA");
-    let ends = endpoints("ZZZ");
-
-    (instructions, packed_graph, starts, ends)
+    Network {
+        instructions,
+        graph: packed_graph,
+        nodes: lut,
+    }
 }
 
-fn lcm(values: &[usize]) -> usize {
-    if values.len() == 1 {
-        return values[0];
+impl Network<'_> {
+    // Nodes matching a pattern; explicitly listed names all have to exist
+    fn select(&self, pattern: &Pattern, role: &'static str) -> Result<Vec<usize>, NetworkError> {
+        if let Pattern::Names(names) = pattern {
+            if let Some(name) = names.iter().find(|&n| !self.nodes.contains_key(n.as_str())) {
+                return Err(NetworkError::UnknownNode(name.clone()));
+            }
+        }
+        let mut selected: Vec<_> = self
+            .nodes
+            .iter()
+            .filter(|(name, _)| pattern.matches(name))
+            .map(|(_, &idx)| idx)
+            .collect();
+        if selected.is_empty() {
+            return Err(NetworkError::NoMatch {
+                role,
+                pattern: pattern.to_string(),
+            });
+        }
+        selected.sort_unstable();
+        Ok(selected)
     }
 
-    fn gcd(a: usize, b: usize) -> usize {
-        if b == 0 {
-            return a;
+    // Start nodes, and whether each node is an end node
+    fn endpoints(
+        &self,
+        start: &Pattern,
+        end: &Pattern,
+    ) -> Result<(Vec<usize>, Vec<bool>), NetworkError> {
+        let starts = self.select(start, "start")?;
+        let mut is_end = vec![false; self.graph.len()];
+        for end in self.select(end, "end")? {
+            is_end[end] = true;
         }
-        gcd(b, a % b)
+        Ok((starts, is_end))
     }
 
-    let a = values[0];
-    let b = lcm(&values[1..]);
-    a * b / gcd(a, b)
+    fn name(&self, node: usize) -> &str {
+        self.nodes.iter().find(|&(_, &idx)| idx == node).unwrap().0
+    }
 }
 
-fn part1(input: &str) -> usize {
-    let (instructions, graph, starts, ends) = parse(input, false);
-    let end = ends[0];
-    let mut steps = 0;
-    let mut current = starts[0];
-    for &next in instructions.iter().cycle() {
-        if current == end {
-            break;
-        }
-        current = graph[current + next];
-        steps += 1;
-    }
-    steps
+// Steps until every start stands on an end node at the same time, or None if
+// that never happens
+fn solve(input: &str, start: &Pattern, end: &Pattern) -> Result<Option<u64>, NetworkError> {
+    let network = parse(input);
+    let (starts, is_end) = network.endpoints(start, end)?;
+    let ghosts: Vec<_> = starts
+        .into_iter()
+        .map(|start| Ghost::trace(&network.instructions, &network.graph, start, &is_end))
+        .collect();
+    Ok(first_common_hit(&ghosts))
 }
 
-fn part2(input: &str) -> usize {
-    let (instructions, graph, starts, ends) = parse(input, true);
+fn part1(input: &str) -> Result<Option<u64>, NetworkError> {
+    let pattern = |name: &str| Pattern::Names(vec![name.to_owned()]);
+    solve(input, &pattern("AAA"), &pattern("ZZZ"))
+}
 
-    let mut periods = Vec::new();
-    for mut current in starts.into_iter() {
-        let mut steps: usize = 0;
-        for &next in instructions.iter().cycle() {
-            if ends.contains(&current) {
-                break;
+fn part2(input: &str) -> Result<Option<u64>, NetworkError> {
+    let pattern = |glob: &str| Pattern::Glob(glob.to_owned());
+    solve(input, &pattern("*A"), &pattern("*Z"))
+}
+
+// Where every start is after `steps` steps, and when it first stands on an
+// end node along the way
+fn look_ahead(
+    input: &str,
+    start: &Pattern,
+    end: &Pattern,
+    steps: u64,
+) -> Result<Vec<String>, NetworkError> {
+    let network = parse(input);
+    let (starts, is_end) = network.endpoints(start, end)?;
+    let table = JumpTable::new(&network.instructions, &network.graph, &is_end, steps);
+    Ok(starts
+        .into_iter()
+        .map(|node| {
+            let state = table.state(node);
+            let at = network.name(table.node(table.after(state, steps)));
+            let first_end = match table.first_end(state, steps) {
+                Some(step) => {
+                    let reached = network.name(table.node(table.after(state, step)));
+                    format!("first end {reached} after {step} steps")
+                }
+                None => "no end on the way".into(),
+            };
+            format!(
+                "{}: at {at} after {steps} steps, {first_end}",
+                network.name(node)
+            )
+        })
+        .collect())
+}
+
+const USAGE: &str = "usage: day-08 [--start PATTERN] [--end PATTERN] [--after STEPS]
+PATTERN: NAME[,NAME...] | GLOB (e.g. '*A') | /REGEX/";
+
+// Start and end patterns, defaulting to those of part 2, and the number of
+// steps to look ahead
+fn parse_args(args: &[String]) -> Result<(Pattern, Pattern, Option<u64>), String> {
+    let mut start = Pattern::Glob("*A".into());
+    let mut end = Pattern::Glob("*Z".into());
+    let mut after = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--start" => start = Pattern::parse(args.next().ok_or("--start needs a pattern")?)?,
+            "--end" => end = Pattern::parse(args.next().ok_or("--end needs a pattern")?)?,
+            "--after" => {
+                let steps = args.next().ok_or("--after needs a number of steps")?;
+                let steps = steps
+                    .parse()
+                    .map_err(|_| format!("invalid number of steps {steps:?}"))?;
+                after = Some(steps);
             }
-            current = graph[current + next];
-            steps += 1;
+            _ => return Err(format!("unknown argument {arg:?}")),
+        }
+    }
+    Ok((start, end, after))
+}
+
+fn print_steps(label: &str, steps: Result<Option<u64>, NetworkError>) {
+    match steps {
+        Ok(Some(steps)) => println!("{label}: {steps}"),
+        Ok(None) => println!("{label}: the end nodes are never reached together"),
+        Err(err) => {
+            eprintln!("Error: {err}");
+            std::process::exit(1);
         }
-        periods.push(steps)
     }
-    lcm(&periods[..])
 }
 
 fn main() {
     let text = include_str!("../inputs/input.txt");
-    println!("Part 1: {}", part1(text));
-    println!("Part 2: {}", part2(text));
+
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    if args.is_empty() {
+        print_steps("Part 1", part1(text));
+        print_steps("Part 2", part2(text));
+        return;
+    }
+
+    match parse_args(&args) {
+        Ok((start, end, None)) => print_steps("Steps", solve(text, &start, &end)),
+        Ok((start, end, Some(steps))) => match look_ahead(text, &start, &end, steps) {
+            Ok(lines) => lines.iter().for_each(|line| println!("{line}")),
+            Err(err) => {
+                eprintln!("Error: {err}");
+                std::process::exit(1);
+            }
+        },
+        Err(err) => {
+            eprintln!("{err}");
+            eprintln!("{USAGE}");
+            std::process::exit(1);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -123,7 +245,7 @@ mod tests {
             GGG = (GGG, GGG)\n\
             ZZZ = (ZZZ, ZZZ)";
 
-        assert_eq!(part1(input), 2);
+        assert_eq!(part1(input), Ok(Some(2)));
 
         let input = "\
             LLR\n\
@@ -132,7 +254,7 @@ mod tests {
             BBB = (AAA, ZZZ)\n\
             ZZZ = (ZZZ, ZZZ)";
 
-        assert_eq!(part1(input), 6);
+        assert_eq!(part1(input), Ok(Some(6)));
     }
 
     #[test]
@@ -149,6 +271,142 @@ mod tests {
         22Z = (22B, 22B)\n\
         XXX = (XXX, XXX)";
 
-        assert_eq!(part2(input), 6);
+        assert_eq!(part2(input), Ok(Some(6)));
+    }
+
+    // One step at a time, for as long as it takes
+    fn simulate(input: &str, limit: usize) -> Option<u64> {
+        let network = parse(input);
+        let mut ghosts = network
+            .select(&Pattern::Glob("*A".into()), "start")
+            .unwrap();
+        let ends = network.select(&Pattern::Glob("*Z".into()), "end").unwrap();
+        let graph = &network.graph;
+        for (step, &next) in network.instructions.iter().cycle().enumerate().take(limit) {
+            if ghosts.iter().all(|ghost| ends.contains(ghost)) {
+                return Some(step as u64);
+            }
+            for ghost in &mut ghosts {
+                *ghost = graph[*ghost + next];
+            }
+        }
+        None
+    }
+
+    #[test]
+    fn test_first_hit_is_not_the_period() {
+        // 1A reaches 1Z after 1, 5, 9, ... steps, 2A after 3, 6, 9, ... steps.
+        // Taking the lcm of the first hits would give 3.
+        let input = "\
+            L\n\
+            \n\
+            11A = (11Z, 11Z)\n\
+            11Z = (11C, 11C)\n\
+            11C = (11D, 11D)\n\
+            11D = (11E, 11E)\n\
+            11E = (11Z, 11Z)\n\
+            22A = (22B, 22B)\n\
+            22B = (22C, 22C)\n\
+            22C = (22Z, 22Z)\n\
+            22Z = (22B, 22B)";
+        assert_eq!(part2(input), Ok(Some(9)));
+        assert_eq!(simulate(input, 100), Some(9));
+    }
+
+    #[test]
+    fn test_several_ends_in_a_cycle() {
+        // 1A is on an end node after 2, 3, 6, 7, ... steps, 2A after 7, 14, ...
+        let input = "\
+            L\n\
+            \n\
+            11A = (11B, 11B)\n\
+            11B = (11Y, 11B)\n\
+            11Y = (11Z, 11Z)\n\
+            11Z = (11C, 11C)\n\
+            11C = (11B, 11B)\n\
+            22A = (22B, 22B)\n\
+            22B = (22C, 22C)\n\
+            22C = (22D, 22D)\n\
+            22D = (22E, 22E)\n\
+            22E = (22F, 22F)\n\
+            22F = (22G, 22G)\n\
+            22G = (22Z, 22Z)\n\
+            22Z = (22B, 22B)";
+        assert_eq!(part2(input), Ok(Some(7)));
+        assert_eq!(simulate(input, 100), Some(7));
+    }
+
+    #[test]
+    fn test_early_hit_only() {
+        // 1A passes 1Z once on its way into a loop, which 2A happens to match
+        let input = "\
+            LR\n\
+            \n\
+            11A = (11B, 11B)\n\
+            11B = (11Z, 11Z)\n\
+            11Z = (11C, 11C)\n\
+            11C = (11C, 11C)\n\
+            22A = (22Z, 22Z)\n\
+            22Z = (22Z, 22Z)";
+        assert_eq!(part2(input), Ok(Some(2)));
+
+        // The ends are never reached together; the lcm would claim 3
+        let input = "\
+            L\n\
+            \n\
+            11A = (11Z, 11Z)\n\
+            11Z = (11B, 11B)\n\
+            11B = (11B, 11B)\n\
+            22A = (22B, 22B)\n\
+            22B = (22C, 22C)\n\
+            22C = (22Z, 22Z)\n\
+            22Z = (22B, 22B)";
+        assert_eq!(part2(input), Ok(None));
+        assert_eq!(simulate(input, 100), None);
+    }
+
+    #[test]
+    fn test_patterns() {
+        // Longer labels from another alphabet
+        let input = "\
+            LR\n\
+            \n\
+            start-1 = (mid-1, xx)\n\
+            mid-1 = (xx, goal-1)\n\
+            goal-1 = (mid-1, xx)\n\
+            start-22 = (mid-22, xx)\n\
+            mid-22 = (mid-222, mid-222)\n\
+            mid-222 = (goal-22, goal-22)\n\
+            goal-22 = (mid-22, mid-22)\n\
+            xx = (xx, xx)";
+        let solve = |start, end| {
+            solve(
+                input,
+                &Pattern::parse(start).unwrap(),
+                &Pattern::parse(end).unwrap(),
+            )
+        };
+
+        assert_eq!(solve("start-*", "goal-*"), Ok(Some(6)));
+        assert_eq!(solve("/^start/", "/^goal-[0-9]+$/"), Ok(Some(6)));
+        assert_eq!(solve("start-1", "goal-1"), Ok(Some(2)));
+        assert_eq!(solve("start-1,start-22", "goal-1,goal-22"), Ok(Some(6)));
+        assert_eq!(solve("start-22", "goal-?"), Ok(None));
+
+        assert_eq!(
+            solve("*A", "goal-*"),
+            Err(NetworkError::NoMatch {
+                role: "start",
+                pattern: "*A".into()
+            })
+        );
+        assert_eq!(
+            solve("start-*", "/z$/").unwrap_err().to_string(),
+            "end pattern \"/z$/\" matches no node"
+        );
+        assert_eq!(
+            solve("start-1,start-3", "goal-*"),
+            Err(NetworkError::UnknownNode("start-3".into()))
+        );
     }
 }
@@ -0,0 +1,131 @@
+use std::fmt;
+
+use regex::Regex;
+
+// A set of node names
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    Names(Vec<String>), // "AAA" or "AAA,BBB"
+    Glob(String),       // "*A", with * and ? as wildcards
+    Regex(Regex),       // "/^..A$/"
+}
+
+impl Pattern {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        if let Some(regex) = spec
+            .strip_prefix('/')
+            .and_then(|s| s.strip_suffix('/'))
+            .filter(|s| !s.is_empty())
+        {
+            Regex::new(regex)
+                .map(Self::Regex)
+                .map_err(|err| format!("invalid regex {regex:?}: {err}"))
+        } else if spec.contains(['*', '?']) {
+            Ok(Self::Glob(spec.to_owned()))
+        } else {
+            let names: Vec<_> = spec.split(',').map(|name| name.trim().to_owned()).collect();
+            if names.iter().any(String::is_empty) {
+                return Err(format!("empty node name in {spec:?}"));
+            }
+            Ok(Self::Names(names))
+        }
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        match self {
+            Self::Names(names) => names.iter().any(|n| n == name),
+            Self::Glob(glob) => {
+                let glob: Vec<_> = glob.chars().collect();
+                let name: Vec<_> = name.chars().collect();
+                glob_matches(&glob, &name)
+            }
+            Self::Regex(regex) => regex.is_match(name),
+        }
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Names(names) => write!(f, "{}", names.join(",")),
+            Self::Glob(glob) => write!(f, "{glob}"),
+            Self::Regex(regex) => write!(f, "/{regex}/"),
+        }
+    }
+}
+
+// Greedy matching: on a mismatch, let the last `*` swallow one more char
+// and retry from there, so no backtracking beyond that is needed
+fn glob_matches(glob: &[char], name: &[char]) -> bool {
+    let (mut g, mut n) = (0, 0);
+    let mut star = None; // (position of the last `*` in glob, name position it resumes at)
+    while n < name.len() {
+        match glob.get(g) {
+            Some('*') => {
+                star = Some((g, n));
+                g += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                g += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_g, star_n)) => {
+                    star = Some((star_g, star_n + 1));
+                    g = star_g + 1;
+                    n = star_n + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    glob[g..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob() {
+        let glob = |spec, name| Pattern::parse(spec).unwrap().matches(name);
+        assert!(glob("*A", "AAA"));
+        assert!(glob("*A", "A"));
+        assert!(glob("*A", "11A"));
+        assert!(!glob("*A", "AAZ"));
+        assert!(glob("?1*", "x1"));
+        assert!(!glob("?1*", "1"));
+        assert!(glob("*", ""));
+        assert!(glob("a*b*c", "aXXbYYbc"));
+        assert!(!glob("a*b*c", "aXXbYYbd"));
+        assert!(glob("*?", "x") && !glob("*?", ""));
+        assert!(glob("A**B", "AB"));
+        // Characters, not bytes
+        assert!(glob("?1", "é1"));
+        assert!(glob("*é", "ééé") && !glob("?", "é1"));
+        // No exponential backtracking
+        let name = "a".repeat(100);
+        assert!(!glob(&format!("{}b", "*a".repeat(30)), &name));
+    }
+
+    #[test]
+    fn test_parse() {
+        let pattern = Pattern::parse("AAA, BBB").unwrap();
+        assert!(pattern.matches("AAA") && pattern.matches("BBB") && !pattern.matches("CCC"));
+        assert_eq!(pattern.to_string(), "AAA,BBB");
+
+        let pattern = Pattern::parse("/^[0-9]+A$/").unwrap();
+        assert!(pattern.matches("12A") && !pattern.matches("X2A"));
+        assert_eq!(pattern.to_string(), "/^[0-9]+A$/");
+
+        assert!(Pattern::parse("/(/")
+            .unwrap_err()
+            .starts_with("invalid regex \"(\""));
+        assert_eq!(
+            Pattern::parse("AAA,").unwrap_err(),
+            "empty node name in \"AAA,\""
+        );
+        // A lone slash is a name, not an empty regex
+        assert!(Pattern::parse("/").unwrap().matches("/"));
+    }
+}
@@ -1,4 +1,4 @@
-use std::{collections::HashSet, usize};
+use std::collections::HashSet;
 
 type Matrix = Vec<Vec<bool>>;
 type EncodedMatrix = (Vec<u64>, Vec<u64>);